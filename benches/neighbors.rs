@@ -0,0 +1,106 @@
+// Counts heap allocations made by `Tree::run` on Towers of Hanoi when
+// successors come from `neighbors` versus a streaming `neighbors_into`. Run
+// with `cargo bench --bench neighbors`.
+
+use arbor::{State, Tree};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+// Peg of each disc, smallest disc first.
+#[derive(Hash, Clone, PartialEq, Eq, Debug)]
+struct Towers<const N: usize>([u8; N]);
+
+impl<const N: usize> Towers<N> {
+    fn top(&self, peg: u8) -> Option<usize> {
+        self.0.iter().position(|&p| p == peg)
+    }
+
+    fn successors(&self) -> impl Iterator<Item = Towers<N>> + '_ {
+        (0..3u8).flat_map(move |from| {
+            (0..3u8).filter_map(move |to| {
+                let disc = self.top(from)?;
+                if from == to || self.top(to).is_some_and(|t| t < disc) {
+                    return None;
+                }
+                let mut next = self.clone();
+                next.0[disc] = to;
+                Some(next)
+            })
+        })
+    }
+}
+
+impl<const N: usize> State for Towers<N> {
+    fn neighbors(&self) -> Vec<Rc<Towers<N>>> {
+        self.successors().map(Rc::new).collect()
+    }
+
+    fn is_goal(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Hash, Clone, PartialEq, Eq, Debug)]
+struct Streaming<const N: usize>(Towers<N>);
+
+impl<const N: usize> State for Streaming<N> {
+    fn neighbors(&self) -> Vec<Rc<Streaming<N>>> {
+        let mut result = Vec::new();
+        self.neighbors_into(&mut result);
+        result
+    }
+
+    fn is_goal(&self) -> bool {
+        false
+    }
+
+    fn neighbors_into(&self, out: &mut Vec<Rc<Streaming<N>>>) {
+        out.extend(self.0.successors().map(|t| Rc::new(Streaming(t))));
+    }
+}
+
+fn measure<F: FnOnce() -> usize>(name: &str, f: F) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let states = f();
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "{name:>10}: {states} states in {elapsed:?}, {allocations} allocations ({:.2} per state)",
+        allocations as f64 / states as f64
+    );
+}
+
+fn main() {
+    println!("Towers of Hanoi, 3 pegs, 10 discs, full exploration");
+    measure("neighbors", || {
+        let mut tree = Tree::new(Rc::new(Towers([0u8; 10])));
+        assert!(tree.run().is_none());
+        tree.stats().visited
+    });
+    measure("streaming", || {
+        let mut tree = Tree::new(Rc::new(Streaming(Towers([0u8; 10]))));
+        assert!(tree.run().is_none());
+        tree.stats().visited
+    });
+}
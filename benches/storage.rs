@@ -0,0 +1,106 @@
+// Compares `Tree` with `CompactTree` on Towers of Hanoi: wall time and peak
+// heap use per visited state. Run with `cargo bench --bench storage`.
+
+use arbor::{CompactTree, State, Tree};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[derive(Hash, Clone, PartialEq, Eq, Debug)]
+struct Towers {
+    pegs: Vec<Vec<usize>>,
+}
+
+impl Towers {
+    fn new(pegs: usize, discs: usize) -> Towers {
+        let mut result = Towers { pegs: Vec::new() };
+        result.pegs.push((0..discs).collect::<Vec<usize>>());
+        for _ in 1..pegs {
+            result.pegs.push(Vec::new());
+        }
+        result
+    }
+
+    fn move_disc(&self, from: usize, to: usize) -> Option<Towers> {
+        if from == to
+            || self.pegs[from].is_empty()
+            || (!self.pegs[to].is_empty()
+                && self.pegs[from].last().unwrap() < self.pegs[to].last().unwrap())
+        {
+            return None;
+        }
+        let mut result = self.clone();
+        let moved = result.pegs[from].pop().unwrap();
+        result.pegs[to].push(moved);
+        Some(result)
+    }
+}
+
+impl State for Towers {
+    fn neighbors(&self) -> Vec<Rc<Towers>> {
+        let mut result = Vec::new();
+        for i in 0..self.pegs.len() {
+            for j in 0..self.pegs.len() {
+                if let Some(neighbor) = self.move_disc(i, j) {
+                    result.push(Rc::new(neighbor));
+                }
+            }
+        }
+        result
+    }
+
+    fn is_goal(&self) -> bool {
+        false
+    }
+}
+
+fn measure<F: FnOnce() -> usize>(name: &str, f: F) {
+    let base = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    let start = Instant::now();
+    let states = f();
+    let elapsed = start.elapsed();
+    let peak = PEAK.load(Ordering::Relaxed) - base;
+    println!(
+        "{name:>8}: {states} states in {elapsed:?}, peak {peak} bytes ({:.1} per state)",
+        peak as f64 / states as f64
+    );
+}
+
+fn main() {
+    for (pegs, discs) in [(3, 10), (4, 8)] {
+        println!("Towers of Hanoi, {pegs} pegs, {discs} discs, full exploration");
+        measure("Tree", || {
+            let mut tree = Tree::new(Rc::new(Towers::new(pegs, discs)));
+            assert!(tree.run().is_none());
+            tree.stats().visited
+        });
+        measure("Compact", || {
+            let mut tree = CompactTree::new(Rc::new(Towers::new(pegs, discs)));
+            assert!(tree.run().is_none());
+            tree.len()
+        });
+    }
+}
@@ -0,0 +1,250 @@
+use ahash::HashMap;
+use std::rc::Rc;
+
+use crate::{ClosedSet, Goal, State};
+
+// Distance from the start, number of shortest paths from the start (saturating
+// at `usize::MAX`) and every parent at the previous distance.
+type Layered<T> = (usize, usize, Vec<Rc<T>>);
+
+/// Finds every shortest path to the nearest goals. Visited states are kept in
+/// a `C` built by `F` at the start of each run, which can be replaced with
+/// `with_closed_set`; its values are the distance, the number of shortest
+/// paths and the parents of each state.
+pub struct AllShortestPaths<
+    T: State,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Rc<T>, Layered<T>> = HashMap<Rc<T>, Layered<T>>,
+    F: Fn() -> C = fn() -> C,
+> {
+    start: Rc<T>,
+    goal: G,
+    closed: F,
+}
+
+impl<T: State> AllShortestPaths<T> {
+    pub fn new(start: Rc<T>) -> AllShortestPaths<T> {
+        AllShortestPaths {
+            start,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            closed: HashMap::default as fn() -> HashMap<Rc<T>, Layered<T>>,
+        }
+    }
+}
+
+impl<T, G, C, F> AllShortestPaths<T, G, C, F>
+where
+    T: State,
+    G: Goal<T>,
+    C: ClosedSet<Rc<T>, Layered<T>>,
+    F: Fn() -> C,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> AllShortestPaths<T, H, C, F> {
+        AllShortestPaths {
+            start: self.start,
+            goal,
+            closed: self.closed,
+        }
+    }
+
+    /// Calls `closed` at the start of each run for the set to keep visited
+    /// states in.
+    pub fn with_closed_set<D, E>(self, closed: E) -> AllShortestPaths<T, G, D, E>
+    where
+        D: ClosedSet<Rc<T>, Layered<T>>,
+        E: Fn() -> D,
+    {
+        AllShortestPaths {
+            start: self.start,
+            goal: self.goal,
+            closed,
+        }
+    }
+
+    /// Searches layer by layer, keeping every parent one layer up, and stops
+    /// once the first layer containing a goal is complete.
+    pub fn run(&mut self) -> Option<ShortestPaths<T, C>> {
+        let mut visited = (self.closed)();
+        visited.insert(Rc::clone(&self.start), (0, 1, Vec::new()));
+        let mut frontier = vec![Rc::clone(&self.start)];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            let goals: Vec<Rc<T>> = frontier
+                .iter()
+                .filter(|t| self.goal.is_goal(t))
+                .cloned()
+                .collect();
+            if !goals.is_empty() {
+                return Some(ShortestPaths {
+                    visited,
+                    goals,
+                    depth,
+                });
+            }
+            let mut next = Vec::new();
+            let mut successors = Vec::new();
+            for current in frontier {
+                // Every parent of `current` is in an earlier layer, so its
+                // count is final by now.
+                let count = visited.get(&current).unwrap().1;
+                current.neighbors_into(&mut successors);
+                for t in successors.drain(..) {
+                    match visited.get_mut(&t) {
+                        Some((d, paths, parents)) => {
+                            if *d == depth + 1 && !parents.contains(&current) {
+                                *paths = paths.saturating_add(count);
+                                parents.push(Rc::clone(&current));
+                            }
+                        }
+                        None => {
+                            let parents = vec![Rc::clone(&current)];
+                            visited.insert(Rc::clone(&t), (depth + 1, count, parents));
+                            next.push(t);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+            depth += 1;
+        }
+        None
+    }
+}
+
+pub struct ShortestPaths<T: State, C: ClosedSet<Rc<T>, Layered<T>> = HashMap<Rc<T>, Layered<T>>> {
+    visited: C,
+    goals: Vec<Rc<T>>,
+    depth: usize,
+}
+
+impl<T: State, C: ClosedSet<Rc<T>, Layered<T>>> ShortestPaths<T, C> {
+    /// Goal states at the shortest distance, in discovery order.
+    pub fn goals(&self) -> &[Rc<T>] {
+        &self.goals
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of distinct shortest paths to any of the goals, saturating at
+    /// `usize::MAX`.
+    pub fn count(&self) -> usize {
+        self.goals.iter().fold(0usize, |sum, g| {
+            sum.saturating_add(self.visited.get(g).unwrap().1)
+        })
+    }
+
+    /// Lazily enumerates every shortest path, one goal after another.
+    pub fn paths(&self) -> Paths<'_, T, C> {
+        Paths {
+            paths: self,
+            goal: 0,
+            stack: Vec::new(),
+        }
+    }
+}
+
+pub struct Paths<'a, T: State, C: ClosedSet<Rc<T>, Layered<T>> = HashMap<Rc<T>, Layered<T>>> {
+    paths: &'a ShortestPaths<T, C>,
+    goal: usize,
+    // Current partial path from a goal towards the start, with the index of
+    // the next parent to try for each state.
+    stack: Vec<(&'a Rc<T>, usize)>,
+}
+
+impl<T: State, C: ClosedSet<Rc<T>, Layered<T>>> Iterator for Paths<'_, T, C> {
+    type Item = Vec<Rc<T>>;
+
+    fn next(&mut self) -> Option<Vec<Rc<T>>> {
+        loop {
+            let Some((node, index)) = self.stack.last_mut() else {
+                let goal = self.paths.goals.get(self.goal)?;
+                self.goal += 1;
+                self.stack.push((goal, 0));
+                continue;
+            };
+            let parents = &self.paths.visited.get(node).unwrap().2;
+            if parents.is_empty() {
+                let path = self.stack.iter().rev().map(|(t, _)| Rc::clone(t)).collect();
+                self.stack.pop();
+                return Some(path);
+            }
+            if let Some(parent) = parents.get(*index) {
+                *index += 1;
+                self.stack.push((parent, 0));
+            } else {
+                self.stack.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Towers};
+    use std::collections::BTreeMap;
+    use std::hash::RandomState;
+
+    #[derive(Hash, Clone, PartialEq, Eq, Debug)]
+    struct Point(u32, u32);
+
+    impl State for Point {
+        fn neighbors(&self) -> Vec<Rc<Point>> {
+            let mut result = Vec::new();
+            if self.0 < 2 {
+                result.push(Rc::new(Point(self.0 + 1, self.1)));
+            }
+            if self.1 < 2 {
+                result.push(Rc::new(Point(self.0, self.1 + 1)));
+            }
+            result
+        }
+
+        fn is_goal(&self) -> bool {
+            self.0 == 2 && self.1 == 2
+        }
+    }
+
+    #[test]
+    fn test_grid() {
+        let paths = AllShortestPaths::new(Rc::new(Point(0, 0))).run().unwrap();
+        assert_eq!(4, paths.depth());
+        assert_eq!(6, paths.count());
+        let mut all: Vec<Vec<Rc<Point>>> = paths.paths().collect();
+        assert_eq!(6, all.len());
+        all.sort_by_key(|p| p.iter().map(|t| (t.0, t.1)).collect::<Vec<_>>());
+        all.dedup();
+        assert_eq!(6, all.len());
+        for path in all {
+            assert_eq!(5, path.len());
+            assert_eq!(Point(0, 0), *path[0]);
+            assert!(is_path(&path));
+        }
+    }
+
+    #[test]
+    fn test_hanoi_unique() {
+        for d in 1..6 {
+            let paths = AllShortestPaths::new(Rc::new(Towers::new(3, d)))
+                .run()
+                .unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, paths.depth());
+            assert_eq!(1, paths.count());
+            assert_eq!(1, paths.paths().count());
+            let ordered = AllShortestPaths::new(Rc::new(Towers::new(3, d)))
+                .with_closed_set(BTreeMap::new)
+                .run()
+                .unwrap();
+            assert!(ordered.paths().eq(paths.paths()));
+            let sized = AllShortestPaths::new(Rc::new(Towers::new(3, d)))
+                .with_closed_set(|| {
+                    std::collections::HashMap::with_capacity_and_hasher(64, RandomState::new())
+                })
+                .run()
+                .unwrap();
+            assert!(sized.paths().eq(paths.paths()));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use ahash::RandomState;
+use core::hash::{BuildHasher, Hash};
+use std::collections::HashMap;
+
+const NONE: u32 = u32::MAX;
+
+/// Interns states, giving each distinct one a dense `u32` id. States are
+/// stored once; the lookup table only maps each hash to the most recently
+/// interned id with that hash, and older ids are chained through `next`. States are hashed
+/// with `S`, `ahash` unless replaced with `with_hasher`.
+pub struct Arena<T, S = RandomState> {
+    states: Vec<T>,
+    next: Vec<u32>,
+    heads: HashMap<u64, u32, S>,
+    hasher: S,
+}
+
+impl<T: Hash + Eq> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> Arena<T, S> {
+    pub fn with_hasher(hasher: S) -> Arena<T, S> {
+        Arena {
+            states: Vec::new(),
+            next: Vec::new(),
+            heads: HashMap::default(),
+            hasher,
+        }
+    }
+
+    /// Moves the states into an arena hashing with `hasher`. Ids are kept.
+    pub fn rehash<R: BuildHasher + Default>(self, hasher: R) -> Arena<T, R> {
+        let mut arena = Arena::with_hasher(hasher);
+        for state in self.states {
+            arena.intern(state);
+        }
+        arena
+    }
+
+    pub fn find(&self, state: &T) -> Option<u32> {
+        let mut id = *self.heads.get(&self.hasher.hash_one(state))?;
+        while id != NONE {
+            if self.states[id as usize] == *state {
+                return Some(id);
+            }
+            id = self.next[id as usize];
+        }
+        None
+    }
+
+    /// Returns the id of `state` and whether it was newly added.
+    pub fn intern(&mut self, state: T) -> (u32, bool) {
+        let hash = self.hasher.hash_one(&state);
+        let head = self.heads.get(&hash).copied().unwrap_or(NONE);
+        let mut id = head;
+        while id != NONE {
+            if self.states[id as usize] == state {
+                return (id, false);
+            }
+            id = self.next[id as usize];
+        }
+        let id = self.states.len();
+        assert!(id < NONE as usize, "arena is full");
+        let id = id as u32;
+        self.states.push(state);
+        self.next.push(head);
+        self.heads.insert(hash, id);
+        (id, true)
+    }
+
+    pub fn get(&self, id: u32) -> &T {
+        &self.states[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> Default for Arena<T, S> {
+    fn default() -> Arena<T, S> {
+        Arena::with_hasher(S::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut arena = Arena::new();
+        assert_eq!((0, true), arena.intern("a"));
+        assert_eq!((1, true), arena.intern("b"));
+        assert_eq!((0, false), arena.intern("a"));
+        assert_eq!(Some(1), arena.find(&"b"));
+        assert_eq!(None, arena.find(&"c"));
+        assert_eq!("b", *arena.get(1));
+        assert_eq!(2, arena.len());
+    }
+
+    // Every value hashes alike, so lookups must walk the collision chain.
+    #[derive(PartialEq, Eq, Debug)]
+    struct Collide(u32);
+
+    impl Hash for Collide {
+        fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+            0u8.hash(state);
+        }
+    }
+
+    #[test]
+    fn test_collisions() {
+        let mut arena = Arena::new();
+        for i in 0..10 {
+            assert_eq!((i, true), arena.intern(Collide(i)));
+        }
+        for i in 0..10 {
+            assert_eq!(Some(i), arena.find(&Collide(i)));
+            assert_eq!((i, false), arena.intern(Collide(i)));
+        }
+    }
+}
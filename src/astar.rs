@@ -0,0 +1,247 @@
+use ahash::{HashMap, HashMapExt};
+use core::marker::PhantomData;
+use std::collections::BinaryHeap;
+use std::rc::Rc;
+
+use crate::weighted::{Cost, Entry, WeightedState};
+use crate::{path_to, ClosedSet, Goal, OpenList};
+
+pub trait Heuristic: WeightedState {
+    /// Estimated remaining cost to the nearest goal. It must never
+    /// overestimate for the returned path to be optimal.
+    fn heuristic(&self) -> Self::Cost;
+}
+
+/// Best-first search on cost plus heuristic. Storage can be replaced as for
+/// `Dijkstra`.
+pub struct AStar<
+    T: Heuristic,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>> = HashMap<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Entry<T, <T as WeightedState>::Cost>> = BinaryHeap<
+        Entry<T, <T as WeightedState>::Cost>,
+    >,
+    B: ClosedSet<Rc<T>, <T as WeightedState>::Cost> = HashMap<Rc<T>, <T as WeightedState>::Cost>,
+> {
+    heap: Q,
+    best: B,
+    visited: C,
+    seq: usize,
+    consistent: bool,
+    reopened: usize,
+    goal: G,
+    state: PhantomData<T>,
+}
+
+impl<T: Heuristic> AStar<T> {
+    pub fn new(start: Rc<T>) -> AStar<T> {
+        let mut search = AStar {
+            heap: BinaryHeap::new(),
+            best: HashMap::new(),
+            visited: HashMap::new(),
+            seq: 0,
+            consistent: true,
+            reopened: 0,
+            goal: <T as WeightedState>::is_goal as fn(&T) -> bool,
+            state: PhantomData,
+        };
+        search.best.insert(Rc::clone(&start), T::Cost::zero());
+        let f = start.heuristic();
+        search.push(start, None, f);
+        search
+    }
+}
+
+impl<T, G, C, Q, B> AStar<T, G, C, Q, B>
+where
+    T: Heuristic,
+    G: Goal<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Entry<T, T::Cost>>,
+    B: ClosedSet<Rc<T>, T::Cost>,
+{
+    /// The heuristic must still be admissible for the new goal for the
+    /// returned path to be optimal.
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> AStar<T, H, C, Q, B> {
+        AStar {
+            heap: self.heap,
+            best: self.best,
+            visited: self.visited,
+            seq: self.seq,
+            consistent: self.consistent,
+            reopened: self.reopened,
+            goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_closed_set<D>(self, mut closed: D) -> AStar<T, G, D, Q, B>
+    where
+        D: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    {
+        for (t, prev) in self.visited.iter() {
+            closed.insert(Rc::clone(t), prev.clone());
+        }
+        AStar {
+            heap: self.heap,
+            best: self.best,
+            visited: closed,
+            seq: self.seq,
+            consistent: self.consistent,
+            reopened: self.reopened,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_open_list<R>(mut self, mut open: R) -> AStar<T, G, C, R, B>
+    where
+        R: OpenList<Entry<T, T::Cost>>,
+    {
+        while let Some(entry) = self.heap.pop() {
+            open.push(entry);
+        }
+        AStar {
+            heap: open,
+            best: self.best,
+            visited: self.visited,
+            seq: self.seq,
+            consistent: self.consistent,
+            reopened: self.reopened,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_cost_map<A>(self, mut costs: A) -> AStar<T, G, C, Q, A>
+    where
+        A: ClosedSet<Rc<T>, T::Cost>,
+    {
+        for (t, &cost) in self.best.iter() {
+            costs.insert(Rc::clone(t), cost);
+        }
+        AStar {
+            heap: self.heap,
+            best: costs,
+            visited: self.visited,
+            seq: self.seq,
+            consistent: self.consistent,
+            reopened: self.reopened,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    /// False once some edge `a -> b` was seen with
+    /// `a.heuristic() > cost(a, b) + b.heuristic()`. Closed states are only
+    /// ever re-opened after that happens.
+    pub fn consistent(&self) -> bool {
+        self.consistent
+    }
+
+    pub fn reopened(&self) -> usize {
+        self.reopened
+    }
+
+    fn push(&mut self, node: Rc<T>, prev: Option<Rc<T>>, f: T::Cost) {
+        self.heap.push(Entry {
+            cost: f,
+            seq: self.seq,
+            node,
+            prev,
+        });
+        self.seq += 1;
+    }
+
+    pub fn run(&mut self) -> Option<(Vec<Rc<T>>, T::Cost)> {
+        while let Some(Entry { node, prev, .. }) = self.heap.pop() {
+            if self.visited.contains(&node) {
+                continue;
+            }
+            self.visited.insert(Rc::clone(&node), prev);
+            let g = *self.best.get(&node).unwrap();
+            if self.goal.is_goal(&node) {
+                return Some((path_to(&self.visited, &node), g));
+            }
+            let h = node.heuristic();
+            for (t, step) in node.neighbors() {
+                let th = t.heuristic();
+                if h > step + th {
+                    self.consistent = false;
+                }
+                let next = g + step;
+                if self.best.get(&t).is_some_and(|&b| b <= next) {
+                    continue;
+                }
+                if self.visited.remove(&t).is_some() {
+                    self.reopened += 1;
+                }
+                self.best.insert(Rc::clone(&t), next);
+                self.push(t, Some(Rc::clone(&node)), next + th);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+
+    #[derive(Hash, Clone, PartialEq, Eq, Debug)]
+    struct Node(char);
+
+    // Admissible but inconsistent around `B`, so `A` is first closed through
+    // the expensive edge from `S` and has to be re-opened.
+    impl WeightedState for Node {
+        type Cost = u32;
+
+        fn neighbors(&self) -> Vec<(Rc<Node>, u32)> {
+            let edges: &[(char, u32)] = match self.0 {
+                'S' => &[('A', 4), ('B', 1)],
+                'B' => &[('A', 1)],
+                'A' => &[('G', 5)],
+                _ => &[],
+            };
+            edges.iter().map(|&(c, w)| (Rc::new(Node(c)), w)).collect()
+        }
+
+        fn is_goal(&self) -> bool {
+            self.0 == 'G'
+        }
+    }
+
+    impl Heuristic for Node {
+        fn heuristic(&self) -> u32 {
+            if self.0 == 'B' {
+                4
+            } else {
+                0
+            }
+        }
+    }
+
+    #[test]
+    fn test_hanoi() {
+        for d in 1..7 {
+            let mut search = AStar::new(Rc::new(Towers::new(3, d)));
+            let (path, cost) = search.run().unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, cost);
+            assert_eq!(cost, path.len() - 1);
+            assert!(search.consistent());
+            assert_eq!(0, search.reopened());
+        }
+    }
+
+    #[test]
+    fn test_reopen_inconsistent() {
+        let mut search = AStar::new(Rc::new(Node('S')));
+        let (path, cost) = search.run().unwrap();
+        let names: String = path.iter().map(|n| n.0).collect();
+        assert_eq!("SBAG", names);
+        assert_eq!(7, cost);
+        assert!(!search.consistent());
+        assert_eq!(1, search.reopened());
+    }
+}
@@ -0,0 +1,296 @@
+use core::hash::{BuildHasher, Hash};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+
+/// Storage for expanded states and what the search keeps about them,
+/// usually the parent. Implemented for `HashMap` with any hasher, so the
+/// default `ahash` one can be swapped for `std`'s DoS-resistant `RandomState`,
+/// and for `BTreeMap`, which iterates in a deterministic order.
+///
+/// Every driver that keeps visited states takes one, except where noted:
+///
+/// - `Arena`, and so `CompactTree` and `OwnedTree`, is itself the closed set
+///   and hands out dense ids, so it takes a `BuildHasher` instead.
+/// - `BitState` stores bits, not states; its two hashers are `ahash` with
+///   fixed seeds so that runs are reproducible.
+/// - `ParallelBfs` takes one per shard, but picks the shard with `ahash`.
+/// - `AllShortestPaths`, `Bidirectional`, `FrontierSearch`, `ParallelBfs`
+///   and `reachable` expand whole layers held in a `Vec`, so they take no
+///   `OpenList`.
+pub trait ClosedSet<K, V>: Default {
+    fn get(&self, key: &K) -> Option<&V>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+    fn insert(&mut self, key: K, value: V);
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn len(&self) -> usize;
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> ClosedSet<K, V> for HashMap<K, V, S> {
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        HashMap::insert(self, key, value);
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        HashMap::iter(self)
+    }
+}
+
+impl<K: Ord, V> ClosedSet<K, V> for BTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        BTreeMap::insert(self, key, value);
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        BTreeMap::iter(self)
+    }
+}
+
+/// States waiting to be expanded. The order `pop` returns them in is up to
+/// the search: `Tree` expects first-in first-out, as `VecDeque` gives, while
+/// `Dijkstra` and `AStar` expect the greatest `Entry` first, as
+/// `BinaryHeap` gives.
+pub trait OpenList<E>: Default {
+    fn push(&mut self, item: E);
+    fn pop(&mut self) -> Option<E>;
+    fn len(&self) -> usize;
+    /// Every queued item, in the order `pop` would return them if the list
+    /// keeps one.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<E> OpenList<E> for VecDeque<E> {
+    fn push(&mut self, item: E) {
+        self.push_back(item);
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        self.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        VecDeque::iter(self)
+    }
+}
+
+impl<E: Ord> OpenList<E> for BinaryHeap<E> {
+    fn push(&mut self, item: E) {
+        BinaryHeap::push(self, item);
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        BinaryHeap::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a E>
+    where
+        E: 'a,
+    {
+        BinaryHeap::iter(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+    use crate::{Dijkstra, Entry, Tree};
+    use std::hash::RandomState;
+    use std::rc::Rc;
+
+    type Parent = Option<Rc<Towers>>;
+
+    // Direct-address table for three pegs, indexed by the peg of each disc.
+    #[derive(Default)]
+    struct Direct {
+        slots: Vec<Option<(Rc<Towers>, Parent)>>,
+        len: usize,
+    }
+
+    impl Direct {
+        fn index(t: &Towers) -> usize {
+            let mut index = 0;
+            for (peg, discs) in t.pegs.iter().enumerate() {
+                for &disc in discs {
+                    index += peg * 3usize.pow(disc as u32);
+                }
+            }
+            index
+        }
+    }
+
+    impl ClosedSet<Rc<Towers>, Parent> for Direct {
+        fn get(&self, key: &Rc<Towers>) -> Option<&Parent> {
+            let slot = self.slots.get(Direct::index(key))?;
+            slot.as_ref().map(|(_, prev)| prev)
+        }
+
+        fn get_mut(&mut self, key: &Rc<Towers>) -> Option<&mut Parent> {
+            let slot = self.slots.get_mut(Direct::index(key))?;
+            slot.as_mut().map(|(_, prev)| prev)
+        }
+
+        fn insert(&mut self, key: Rc<Towers>, value: Parent) {
+            let index = Direct::index(&key);
+            if self.slots.len() <= index {
+                self.slots.resize(index + 1, None);
+            }
+            if self.slots[index].replace((key, value)).is_none() {
+                self.len += 1;
+            }
+        }
+
+        fn remove(&mut self, key: &Rc<Towers>) -> Option<Parent> {
+            let (_, prev) = self.slots.get_mut(Direct::index(key))?.take()?;
+            self.len -= 1;
+            Some(prev)
+        }
+
+        fn len(&self) -> usize {
+            self.len
+        }
+
+        fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Rc<Towers>, &'a Parent)>
+        where
+            Rc<Towers>: 'a,
+            Parent: 'a,
+        {
+            self.slots.iter().flatten().map(|(t, prev)| (t, prev))
+        }
+    }
+
+    // Priority queue kept as a sorted `Vec`, greatest last.
+    struct Sorted<E>(Vec<E>);
+
+    impl<E> Default for Sorted<E> {
+        fn default() -> Sorted<E> {
+            Sorted(Vec::new())
+        }
+    }
+
+    impl<E: Ord> OpenList<E> for Sorted<E> {
+        fn push(&mut self, item: E) {
+            let at = self.0.partition_point(|e| *e < item);
+            self.0.insert(at, item);
+        }
+
+        fn pop(&mut self) -> Option<E> {
+            self.0.pop()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn iter<'a>(&'a self) -> impl Iterator<Item = &'a E>
+        where
+            E: 'a,
+        {
+            self.0.iter().rev()
+        }
+    }
+
+    #[test]
+    fn test_tree_backends() {
+        let start = Rc::new(Towers::new(3, 4));
+        let cleared = |t: &Towers| t.pegs[0].is_empty();
+        let expected: Vec<_> = Tree::new(Rc::clone(&start))
+            .with_goal(cleared)
+            .goals(None)
+            .collect();
+        let mut std_hash = Tree::new(Rc::clone(&start))
+            .with_goal(cleared)
+            .with_closed_set(HashMap::<_, _, RandomState>::default());
+        assert_eq!(expected, std_hash.goals(None).collect::<Vec<_>>());
+        let mut ordered = Tree::new(Rc::clone(&start))
+            .with_goal(cleared)
+            .with_closed_set(BTreeMap::new());
+        assert_eq!(expected, ordered.goals(None).collect::<Vec<_>>());
+        let mut direct = Tree::new(start)
+            .with_goal(cleared)
+            .with_closed_set(Direct::default());
+        assert_eq!(expected, direct.goals(None).collect::<Vec<_>>());
+        assert_eq!(81, direct.stats().visited);
+    }
+
+    #[test]
+    fn test_custom_queue() {
+        for d in 1..5 {
+            let start = Rc::new(Towers::new(4, d));
+            let expected = Dijkstra::new(Rc::clone(&start)).run();
+            let actual = Dijkstra::new(Rc::clone(&start))
+                .with_open_list(Sorted::<Entry<_, usize>>::default())
+                .run();
+            assert_eq!(expected, actual);
+            let ordered = Dijkstra::new(start).with_cost_map(BTreeMap::new()).run();
+            assert_eq!(expected, ordered);
+        }
+    }
+}
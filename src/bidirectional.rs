@@ -0,0 +1,215 @@
+use ahash::HashMap;
+use std::rc::Rc;
+
+use crate::{ClosedSet, State};
+
+pub trait Reversible: State {
+    /// States with a move into `self`. Defaults to `neighbors` for state
+    /// spaces where every move can be undone.
+    fn predecessors(&self) -> Vec<Rc<Self>> {
+        self.neighbors()
+    }
+}
+
+// Parent towards a side's root and distance from it.
+type Reached<T> = (Option<Rc<T>>, usize);
+
+struct Side<T, C> {
+    visited: C,
+    frontier: Vec<Rc<T>>,
+    depth: usize,
+    buffer: Vec<Rc<T>>,
+}
+
+impl<T: Reversible, C: ClosedSet<Rc<T>, Reached<T>>> Side<T, C> {
+    fn new(root: Rc<T>, mut visited: C) -> Side<T, C> {
+        visited.insert(Rc::clone(&root), (None, 0));
+        Side {
+            visited,
+            frontier: vec![root],
+            depth: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    // Expands the whole frontier layer and returns the state seen by `other`
+    // with the shortest combined distance, if any.
+    fn expand(&mut self, other: &Side<T, C>, forward: bool) -> Option<Rc<T>> {
+        let mut meeting: Option<(Rc<T>, usize)> = None;
+        let mut next = Vec::new();
+        for current in self.frontier.drain(..) {
+            if forward {
+                current.neighbors_into(&mut self.buffer);
+            } else {
+                self.buffer.extend(current.predecessors());
+            }
+            for t in self.buffer.drain(..) {
+                if self.visited.contains(&t) {
+                    continue;
+                }
+                self.visited
+                    .insert(Rc::clone(&t), (Some(Rc::clone(&current)), self.depth + 1));
+                if let Some((_, d)) = other.visited.get(&t) {
+                    if meeting.as_ref().is_none_or(|(_, m)| d < m) {
+                        meeting = Some((Rc::clone(&t), *d));
+                    }
+                }
+                next.push(t);
+            }
+        }
+        self.frontier = next;
+        self.depth += 1;
+        meeting.map(|(t, _)| t)
+    }
+
+    // The chain of states from `node` back to this side's root.
+    fn chain(&self, node: &Rc<T>) -> Vec<Rc<T>> {
+        let mut result = vec![Rc::clone(node)];
+        let mut current = node;
+        while let Some((Some(prev), _)) = self.visited.get(current) {
+            current = prev;
+            result.push(Rc::clone(current));
+        }
+        result
+    }
+}
+
+/// Breadth-first search from both `start` and `goal`, expanding whichever
+/// side has the smaller frontier. Each side keeps its visited states in a
+/// `C` built by `F`, which can be replaced with `with_closed_set`.
+pub struct Bidirectional<
+    T: Reversible,
+    C: ClosedSet<Rc<T>, Reached<T>> = HashMap<Rc<T>, Reached<T>>,
+    F: Fn() -> C = fn() -> C,
+> {
+    start: Rc<T>,
+    goal: Rc<T>,
+    forward: Side<T, C>,
+    backward: Side<T, C>,
+    closed: F,
+}
+
+impl<T: Reversible> Bidirectional<T> {
+    pub fn new(start: Rc<T>, goal: Rc<T>) -> Bidirectional<T> {
+        Bidirectional::with_sets(start, goal, HashMap::default)
+    }
+}
+
+impl<T, C, F> Bidirectional<T, C, F>
+where
+    T: Reversible,
+    C: ClosedSet<Rc<T>, Reached<T>>,
+    F: Fn() -> C,
+{
+    fn with_sets(start: Rc<T>, goal: Rc<T>, closed: F) -> Bidirectional<T, C, F> {
+        Bidirectional {
+            forward: Side::new(Rc::clone(&start), closed()),
+            backward: Side::new(Rc::clone(&goal), closed()),
+            start,
+            goal,
+            closed,
+        }
+    }
+
+    /// Calls `closed` twice per run, once for the states reached from each
+    /// end.
+    pub fn with_closed_set<D, E>(self, closed: E) -> Bidirectional<T, D, E>
+    where
+        D: ClosedSet<Rc<T>, Reached<T>>,
+        E: Fn() -> D,
+    {
+        Bidirectional::with_sets(self.start, self.goal, closed)
+    }
+
+    /// Each call searches afresh from both ends, so repeated calls return
+    /// the same path.
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        self.forward = Side::new(Rc::clone(&self.start), (self.closed)());
+        self.backward = Side::new(Rc::clone(&self.goal), (self.closed)());
+        if self.start == self.goal {
+            return Some(vec![Rc::clone(&self.start)]);
+        }
+        while !self.forward.frontier.is_empty() && !self.backward.frontier.is_empty() {
+            let meeting = if self.forward.frontier.len() <= self.backward.frontier.len() {
+                self.forward.expand(&self.backward, true)
+            } else {
+                self.backward.expand(&self.forward, false)
+            };
+            if let Some(node) = meeting {
+                let mut path = self.forward.chain(&node);
+                path.reverse();
+                path.extend(self.backward.chain(&node).into_iter().skip(1));
+                return Some(path);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Towers};
+    use std::collections::BTreeMap;
+
+    // Moves are `+1` and `*2`, so predecessors differ from neighbors.
+    #[derive(Hash, Clone, PartialEq, Eq, Debug)]
+    struct Number(u32);
+
+    impl State for Number {
+        fn neighbors(&self) -> Vec<Rc<Number>> {
+            vec![Rc::new(Number(self.0 + 1)), Rc::new(Number(self.0 * 2))]
+        }
+
+        fn is_goal(&self) -> bool {
+            false
+        }
+    }
+
+    impl Reversible for Number {
+        fn predecessors(&self) -> Vec<Rc<Number>> {
+            let mut result = Vec::new();
+            if self.0 > 0 {
+                result.push(Rc::new(Number(self.0 - 1)));
+            }
+            if self.0.is_multiple_of(2) {
+                result.push(Rc::new(Number(self.0 / 2)));
+            }
+            result
+        }
+    }
+
+    #[test]
+    fn test_hanoi() {
+        for d in 1..7 {
+            let start = Rc::new(Towers::new(3, d));
+            let mut goal = Towers::new(3, 0);
+            goal.pegs[2] = (0..d).collect();
+            let mut search = Bidirectional::new(start, Rc::new(goal));
+            let path = search.run().unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, path.len() - 1);
+            assert!(path.last().unwrap().is_goal());
+            assert!(is_path(&path));
+            let mut ordered = search.with_closed_set(BTreeMap::new);
+            assert_eq!(Some(path), ordered.run());
+        }
+    }
+
+    #[test]
+    fn test_run_twice() {
+        let start = Rc::new(Number(1));
+        let mut search = Bidirectional::new(Rc::clone(&start), Rc::new(Number(10)));
+        let first = search.run().unwrap();
+        assert_eq!(start, first[0]);
+        assert_eq!(Some(first), search.run());
+    }
+
+    #[test]
+    fn test_predecessors() {
+        let path = Bidirectional::new(Rc::new(Number(1)), Rc::new(Number(100)))
+            .run()
+            .unwrap();
+        let values: Vec<u32> = path.iter().map(|n| n.0).collect();
+        assert_eq!(vec![1, 2, 3, 6, 12, 24, 25, 50, 100], values);
+    }
+}
@@ -0,0 +1,231 @@
+use ahash::RandomState;
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+use crate::{Goal, State};
+
+// A Bloom filter over `1 << log2` bits probed at `hashes` positions, derived
+// from two base hashes. Seeds are fixed so runs are reproducible.
+struct Filter {
+    bits: Vec<u64>,
+    mask: u64,
+    hashes: u32,
+    set: usize,
+    first: RandomState,
+    second: RandomState,
+}
+
+impl Filter {
+    fn new(log2: u32, hashes: u32) -> Filter {
+        let len = 1u64 << log2;
+        Filter {
+            bits: vec![0; len.div_ceil(64) as usize],
+            mask: len - 1,
+            hashes,
+            set: 0,
+            first: RandomState::with_seeds(1, 2, 3, 4),
+            second: RandomState::with_seeds(5, 6, 7, 8),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.mask as usize + 1
+    }
+
+    // Sets the bits for `state` and returns whether any of them was clear.
+    fn insert<T: State>(&mut self, state: &T) -> bool {
+        let h1 = self.first.hash_one(state);
+        let h2 = self.second.hash_one(state) | 1;
+        let mut new = false;
+        for i in 0..self.hashes as u64 {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) & self.mask;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            if self.bits[word] & mask == 0 {
+                self.bits[word] |= mask;
+                self.set += 1;
+                new = true;
+            }
+        }
+        new
+    }
+
+    // Chance that a state not yet inserted finds all its bits already set.
+    fn false_positive(&self) -> f64 {
+        (self.set as f64 / self.len() as f64).powi(self.hashes as i32)
+    }
+}
+
+/// Depth-first search that records visited states only as bits in a fixed
+/// size Bloom filter, as in SPIN's bitstate mode. Memory use does not grow
+/// with the state space, but a new state whose bits happen to be set already
+/// is taken for a duplicate and skipped, so some states may never be
+/// explored. Paths found are real but not necessarily shortest.
+pub struct BitState<T: State, G: Goal<T> = fn(&T) -> bool> {
+    start: Rc<T>,
+    goal: G,
+    log2: u32,
+    hashes: u32,
+    filter: Option<Filter>,
+    path: Vec<Rc<T>>,
+    stack: Vec<IntoIter<Rc<T>>>,
+    stored: usize,
+    missed: f64,
+}
+
+impl<T: State> BitState<T> {
+    /// Uses 2^27 bits (16 MiB) and 3 hash functions.
+    pub fn new(start: Rc<T>) -> BitState<T> {
+        BitState {
+            start,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            log2: 27,
+            hashes: 3,
+            filter: None,
+            path: Vec::new(),
+            stack: Vec::new(),
+            stored: 0,
+            missed: 0.0,
+        }
+    }
+}
+
+impl<T: State, G: Goal<T>> BitState<T, G> {
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> BitState<T, H> {
+        BitState {
+            start: self.start,
+            goal,
+            log2: self.log2,
+            hashes: self.hashes,
+            filter: self.filter,
+            path: self.path,
+            stack: self.stack,
+            stored: self.stored,
+            missed: self.missed,
+        }
+    }
+
+    /// Sizes the filter to `1 << log2` bits. Has no effect once the search
+    /// has started.
+    pub fn with_bits(mut self, log2: u32) -> BitState<T, G> {
+        self.log2 = log2.clamp(6, 40);
+        self
+    }
+
+    pub fn with_hashes(mut self, hashes: u32) -> BitState<T, G> {
+        self.hashes = hashes.max(1);
+        self
+    }
+
+    /// Number of states stored in the filter and expanded.
+    pub fn stored(&self) -> usize {
+        self.stored
+    }
+
+    /// Chance that a state seen now for the first time would be wrongly
+    /// skipped, given how full the filter is.
+    pub fn omission_probability(&self) -> f64 {
+        self.filter.as_ref().map_or(0.0, Filter::false_positive)
+    }
+
+    /// Estimated fraction of the states reached that were stored rather than
+    /// skipped by a false match. States only reachable through skipped ones
+    /// are not accounted for, so this is an upper bound on true coverage.
+    pub fn estimated_coverage(&self) -> f64 {
+        if self.stored == 0 {
+            1.0
+        } else {
+            self.stored as f64 / (self.stored as f64 + self.missed)
+        }
+    }
+
+    // Stores `state` if the filter has not seen it, and accounts for the new
+    // states that may have been skipped at the current fill level: each one
+    // is stored with probability 1 - p, so every store stands for p / (1 - p)
+    // expected misses.
+    fn store(&mut self, state: &T) -> bool {
+        let filter = self.filter.as_mut().unwrap();
+        let p = filter.false_positive();
+        if !filter.insert(state) {
+            return false;
+        }
+        self.stored += 1;
+        if p < 1.0 {
+            self.missed += p / (1.0 - p);
+        }
+        true
+    }
+
+    /// Returns a path to the next goal found; calling `run` again continues
+    /// the search from there.
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        if self.filter.is_none() {
+            self.filter = Some(Filter::new(self.log2, self.hashes));
+            let start = Rc::clone(&self.start);
+            self.store(&start);
+            self.stack.push(start.neighbors().into_iter());
+            self.path.push(start);
+            if self.goal.is_goal(&self.path[0]) {
+                return Some(self.path.clone());
+            }
+        }
+        while let Some(successors) = self.stack.last_mut() {
+            let Some(next) = successors.next() else {
+                self.stack.pop();
+                self.path.pop();
+                continue;
+            };
+            if !self.store(&next) {
+                continue;
+            }
+            self.stack.push(next.neighbors().into_iter());
+            self.path.push(next);
+            if self.goal.is_goal(self.path.last().unwrap()) {
+                return Some(self.path.clone());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reachable;
+    use crate::testing::{is_path, Towers};
+
+    #[test]
+    fn test_exhaustive() {
+        // With ample memory every state is stored, so all goals are found.
+        let start = Rc::new(Towers::new(4, 4));
+        let cleared = |t: &Towers| t.pegs[0].is_empty();
+        let mut search = BitState::new(Rc::clone(&start))
+            .with_bits(20)
+            .with_goal(cleared);
+        let mut goals = 0;
+        while let Some(path) = search.run() {
+            assert_eq!(start, path[0]);
+            assert!(cleared(path.last().unwrap()));
+            assert!(is_path(&path));
+            goals += 1;
+        }
+        let reached = reachable(start);
+        assert_eq!(reached.len(), search.stored());
+        assert_eq!(reached.iter().filter(|(t, _)| cleared(t)).count(), goals);
+        assert!(search.estimated_coverage() > 0.99);
+        assert!(search.omission_probability() < 1e-3);
+    }
+
+    #[test]
+    fn test_saturated() {
+        // A tiny filter fills up and skips states, and says so.
+        let start = Rc::new(Towers::new(4, 5));
+        let mut search = BitState::new(Rc::clone(&start))
+            .with_bits(8)
+            .with_goal(|_: &Towers| false);
+        assert!(search.run().is_none());
+        assert!(search.stored() < reachable(start).len());
+        assert!(search.stored() <= 256);
+        assert!(search.estimated_coverage() < 0.9);
+        assert!(search.omission_probability() > 0.5);
+    }
+}
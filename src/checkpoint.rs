@@ -0,0 +1,147 @@
+use ahash::{HashMap, HashMapExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::{ClosedSet, Goal, Limits, Observer, OpenList, SearchStats, State, Tree};
+
+/// A serializable snapshot of a `Tree`: its queue, parent links and
+/// statistics. States are stored once each and referred to by index.
+///
+/// The goal, limits and observer are not part of the snapshot; set them again
+/// on the restored tree with the usual builder methods.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint<T> {
+    states: Vec<T>,
+    visited: Vec<(usize, Option<usize>)>,
+    queue: Vec<(usize, Option<usize>, usize)>,
+    stats: SearchStats,
+    pruned: Vec<(usize, usize)>,
+    completed: usize,
+}
+
+struct Indexer<'a, T> {
+    states: Vec<&'a T>,
+    index: HashMap<&'a T, usize>,
+}
+
+impl<'a, T: State> Indexer<'a, T> {
+    fn get(&mut self, state: &'a Rc<T>) -> usize {
+        *self.index.entry(&**state).or_insert_with(|| {
+            self.states.push(&**state);
+            self.states.len() - 1
+        })
+    }
+}
+
+impl<T, G, O, C, Q> Tree<T, G, O, C, Q>
+where
+    T: State + Clone,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<(Rc<T>, Option<Rc<T>>, usize)>,
+{
+    pub fn checkpoint(&self) -> Checkpoint<T> {
+        let mut indexer = Indexer {
+            states: Vec::new(),
+            index: HashMap::new(),
+        };
+        let visited = self
+            .visited
+            .iter()
+            .map(|(t, prev)| (indexer.get(t), prev.as_ref().map(|p| indexer.get(p))))
+            .collect();
+        let queue = self
+            .held
+            .iter()
+            .chain(self.queue.iter())
+            .map(|(t, prev, depth)| {
+                (
+                    indexer.get(t),
+                    prev.as_ref().map(|p| indexer.get(p)),
+                    *depth,
+                )
+            })
+            .collect();
+        let pruned = self
+            .pruned
+            .iter()
+            .map(|(t, depth)| (indexer.get(t), *depth))
+            .collect();
+        Checkpoint {
+            states: indexer.states.into_iter().cloned().collect(),
+            visited,
+            queue,
+            stats: self.stats.clone(),
+            pruned,
+            completed: self.completed,
+        }
+    }
+}
+
+impl<T: State> Tree<T> {
+    /// Restores a tree saved with `checkpoint`. Running it gives the same
+    /// results, in the same order, as the original tree would have. Other
+    /// backends can be set again with `with_closed_set` and `with_open_list`.
+    pub fn from_checkpoint(checkpoint: Checkpoint<T>) -> Tree<T> {
+        let states: Vec<Rc<T>> = checkpoint.states.into_iter().map(Rc::new).collect();
+        let link = |i: Option<usize>| i.map(|i| Rc::clone(&states[i]));
+        let mut visited = HashMap::with_capacity(checkpoint.visited.len());
+        for (t, prev) in checkpoint.visited {
+            visited.insert(Rc::clone(&states[t]), link(prev));
+        }
+        let queue: VecDeque<_> = checkpoint
+            .queue
+            .into_iter()
+            .map(|(t, prev, depth)| (Rc::clone(&states[t]), link(prev), depth))
+            .collect();
+        Tree {
+            queue,
+            held: None,
+            visited,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            limits: Limits::default(),
+            stats: checkpoint.stats,
+            pruned: checkpoint
+                .pruned
+                .into_iter()
+                .map(|(t, depth)| (Rc::clone(&states[t]), depth))
+                .collect(),
+            observer: (),
+            completed: checkpoint.completed,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+    use crate::SearchResult;
+
+    #[test]
+    fn test_resume() {
+        let start = Rc::new(Towers::new(4, 3));
+        let cleared = |t: &Towers| t.pegs[0].is_empty();
+        let mut full = Tree::new(Rc::clone(&start)).with_goal(cleared);
+        let expected: Vec<_> = full.goals(None).collect();
+
+        let limits = Limits {
+            max_expanded: Some(40),
+            ..Limits::default()
+        };
+        let mut tree = Tree::new(start).with_goal(cleared).with_limits(limits);
+        let mut actual = Vec::new();
+        while let SearchResult::Found(path) = tree.search() {
+            actual.push(path);
+        }
+        let json = serde_json::to_string(&tree.checkpoint()).unwrap();
+        let checkpoint: Checkpoint<Towers> = serde_json::from_str(&json).unwrap();
+        let mut tree = Tree::from_checkpoint(checkpoint).with_goal(cleared);
+        actual.extend(tree.goals(None));
+        assert_eq!(expected, actual);
+        assert_eq!(full.stats(), tree.stats());
+    }
+}
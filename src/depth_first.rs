@@ -0,0 +1,187 @@
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+use crate::{Goal, State};
+
+enum Outcome<T> {
+    Found(Vec<Rc<T>>),
+    Cutoff,
+    Exhausted,
+}
+
+// Only the current path and the unexplored successors of each state on it are
+// kept; cycles are avoided by checking the path rather than a visited map.
+fn depth_limited<T: State, G: Goal<T>>(
+    start: &Rc<T>,
+    goal: &G,
+    limit: Option<usize>,
+) -> Outcome<T> {
+    if goal.is_goal(start) {
+        return Outcome::Found(vec![Rc::clone(start)]);
+    }
+    let mut cutoff = false;
+    let mut path = vec![Rc::clone(start)];
+    let mut stack: Vec<IntoIter<Rc<T>>> = Vec::new();
+    if limit != Some(0) {
+        stack.push(start.neighbors().into_iter());
+    } else {
+        cutoff = true;
+    }
+    while let Some(successors) = stack.last_mut() {
+        let Some(next) = successors.next() else {
+            stack.pop();
+            path.pop();
+            continue;
+        };
+        if path.contains(&next) {
+            continue;
+        }
+        let depth = path.len();
+        if goal.is_goal(&next) {
+            path.push(next);
+            return Outcome::Found(path);
+        }
+        if limit.is_some_and(|l| depth >= l) {
+            cutoff = true;
+            continue;
+        }
+        stack.push(next.neighbors().into_iter());
+        path.push(next);
+    }
+    if cutoff {
+        Outcome::Cutoff
+    } else {
+        Outcome::Exhausted
+    }
+}
+
+pub struct DepthFirst<T: State, G: Goal<T> = fn(&T) -> bool> {
+    start: Rc<T>,
+    max_depth: Option<usize>,
+    goal: G,
+}
+
+impl<T: State> DepthFirst<T> {
+    pub fn new(start: Rc<T>) -> DepthFirst<T> {
+        DepthFirst {
+            start,
+            max_depth: None,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+        }
+    }
+}
+
+impl<T: State, G: Goal<T>> DepthFirst<T, G> {
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> DepthFirst<T, H> {
+        DepthFirst {
+            start: self.start,
+            max_depth: self.max_depth,
+            goal,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> DepthFirst<T, G> {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Returns the first path found, which is not necessarily the shortest.
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        match depth_limited(&self.start, &self.goal, self.max_depth) {
+            Outcome::Found(path) => Some(path),
+            Outcome::Cutoff | Outcome::Exhausted => None,
+        }
+    }
+}
+
+pub struct IterativeDeepening<T: State, G: Goal<T> = fn(&T) -> bool> {
+    start: Rc<T>,
+    max_depth: Option<usize>,
+    depth: usize,
+    goal: G,
+}
+
+impl<T: State> IterativeDeepening<T> {
+    pub fn new(start: Rc<T>) -> IterativeDeepening<T> {
+        IterativeDeepening {
+            start,
+            max_depth: None,
+            depth: 0,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+        }
+    }
+}
+
+impl<T: State, G: Goal<T>> IterativeDeepening<T, G> {
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> IterativeDeepening<T, H> {
+        IterativeDeepening {
+            start: self.start,
+            max_depth: self.max_depth,
+            depth: self.depth,
+            goal,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> IterativeDeepening<T, G> {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The depth limit of the last iteration that was run.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns a path with the fewest edges, like `Tree::run`.
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        let mut limit = 0;
+        loop {
+            self.depth = limit;
+            match depth_limited(&self.start, &self.goal, Some(limit)) {
+                Outcome::Found(path) => return Some(path),
+                Outcome::Exhausted => return None,
+                Outcome::Cutoff => {}
+            }
+            if self.max_depth.is_some_and(|m| limit >= m) {
+                return None;
+            }
+            limit += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Towers};
+
+    #[test]
+    fn test_depth_first_hanoi() {
+        for d in 1..5 {
+            let path = DepthFirst::new(Rc::new(Towers::new(3, d))).run().unwrap();
+            assert!(path.last().unwrap().is_goal());
+            assert!(is_path(&path));
+        }
+    }
+
+    #[test]
+    fn test_depth_first_max_depth() {
+        let start = Rc::new(Towers::new(3, 3));
+        assert!(DepthFirst::new(Rc::clone(&start))
+            .with_max_depth(6)
+            .run()
+            .is_none());
+        assert!(DepthFirst::new(start).with_max_depth(7).run().is_some());
+    }
+
+    #[test]
+    fn test_iterative_deepening_hanoi() {
+        for d in 1..4 {
+            let mut search = IterativeDeepening::new(Rc::new(Towers::new(3, d)));
+            let path = search.run().unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, path.len() - 1);
+            assert_eq!(path.len() - 1, search.depth());
+            assert!(is_path(&path));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use ahash::HashMap;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::{ClosedSet, State};
+
+/// Every state reachable from `start` with its breadth-first distance,
+/// ignoring `is_goal`.
+pub fn reachable<T: State>(start: Rc<T>) -> Reachable<T> {
+    reachable_in(start, HashMap::default())
+}
+
+/// Like `reachable`, but indexes the states in `index`, which should be
+/// empty.
+pub fn reachable_in<T, C>(start: Rc<T>, index: C) -> Reachable<T, C>
+where
+    T: State,
+    C: ClosedSet<Rc<T>, usize>,
+{
+    let mut result = Reachable {
+        states: Vec::new(),
+        index,
+    };
+    result.index.insert(Rc::clone(&start), 0);
+    result.states.push((start, 0));
+    let mut successors = Vec::new();
+    let mut next = 0;
+    while let Some((current, depth)) = result.states.get(next).cloned() {
+        current.neighbors_into(&mut successors);
+        for t in successors.drain(..) {
+            if !result.index.contains(&t) {
+                result.index.insert(Rc::clone(&t), result.states.len());
+                result.states.push((t, depth + 1));
+            }
+        }
+        next += 1;
+    }
+    result
+}
+
+pub struct Reachable<T: State, C: ClosedSet<Rc<T>, usize> = HashMap<Rc<T>, usize>> {
+    // States in breadth-first order with their distance from the start.
+    states: Vec<(Rc<T>, usize)>,
+    index: C,
+}
+
+impl<T: State, C: ClosedSet<Rc<T>, usize>> Reachable<T, C> {
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn contains(&self, state: &Rc<T>) -> bool {
+        self.index.contains(state)
+    }
+
+    pub fn depth(&self, state: &Rc<T>) -> Option<usize> {
+        self.index.get(state).map(|&i| self.states[i].1)
+    }
+
+    /// States and their depths in breadth-first order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Rc<T>, usize)> {
+        self.states.iter().map(|(t, d)| (t, *d))
+    }
+
+    /// Distance from the start to the farthest reachable state.
+    pub fn max_depth(&self) -> usize {
+        self.states.last().map_or(0, |(_, d)| *d)
+    }
+
+    /// Number of states at each distance from the start.
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.max_depth() + 1];
+        for (_, d) in &self.states {
+            sizes[*d] += 1;
+        }
+        sizes
+    }
+
+    /// Expands every state again to build the reachability graph.
+    pub fn graph(&self) -> StateGraph<T> {
+        let mut edges = Vec::new();
+        let mut successors = Vec::new();
+        for (i, (t, _)) in self.states.iter().enumerate() {
+            t.neighbors_into(&mut successors);
+            for s in successors.drain(..) {
+                edges.push((i, *self.index.get(&s).unwrap()));
+            }
+        }
+        StateGraph {
+            nodes: self.states.clone(),
+            edges,
+        }
+    }
+}
+
+/// The reachability graph of a state space. Node `i` is the `i`-th state in
+/// breadth-first order, with its distance from the start; edges are directed
+/// pairs of node indices, one per move.
+pub struct StateGraph<T> {
+    pub nodes: Vec<(Rc<T>, usize)>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl<T> StateGraph<T> {
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for &(from, to) in &self.edges {
+            adjacency[from].push(to);
+        }
+        adjacency
+    }
+
+    /// Longest shortest path between any two nodes where the second is
+    /// reachable from the first. Runs a breadth-first search from every node.
+    pub fn diameter(&self) -> usize {
+        let adjacency = self.adjacency();
+        let mut diameter = 0;
+        let mut distance = vec![usize::MAX; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for source in 0..self.nodes.len() {
+            distance.fill(usize::MAX);
+            distance[source] = 0;
+            queue.push_back(source);
+            while let Some(u) = queue.pop_front() {
+                diameter = diameter.max(distance[u]);
+                for &v in &adjacency[u] {
+                    if distance[v] == usize::MAX {
+                        distance[v] = distance[u] + 1;
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+        diameter
+    }
+
+    /// Writes the graph in Graphviz DOT format, labelling nodes with `label`
+    /// and their depth.
+    pub fn write_dot<W: Write, F: Fn(&T) -> String>(
+        &self,
+        out: &mut W,
+        label: F,
+    ) -> io::Result<()> {
+        writeln!(out, "digraph {{")?;
+        for (i, (t, depth)) in self.nodes.iter().enumerate() {
+            let text = format!("{}\\n{}", label(t), depth).replace('"', "\\\"");
+            writeln!(out, "    {i} [label=\"{text}\"];")?;
+        }
+        for (from, to) in &self.edges {
+            writeln!(out, "    {from} -> {to};")?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Ring, Towers};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_hanoi_space() {
+        for d in 1..5 {
+            let space = reachable(Rc::new(Towers::new(3, d)));
+            assert_eq!(3usize.pow(d as u32), space.len());
+            assert_eq!(space.len(), space.layer_sizes().iter().sum::<usize>());
+            let mut solved = Towers::new(3, 0);
+            solved.pegs[2] = (0..d).collect();
+            let solved = Rc::new(solved);
+            assert_eq!(Some(2usize.pow(d as u32) - 1), space.depth(&solved));
+            let ordered = reachable_in(Rc::new(Towers::new(3, d)), BTreeMap::new());
+            assert_eq!(space.len(), ordered.len());
+            assert_eq!(space.depth(&solved), ordered.depth(&solved));
+
+            let graph = space.graph();
+            assert_eq!(3 * space.len() - 3, graph.edges.len());
+            assert_eq!(2usize.pow(d as u32) - 1, graph.diameter());
+        }
+    }
+
+    #[test]
+    fn test_ring_dot() {
+        let space = reachable(Rc::new(Ring::new(3)));
+        assert_eq!(vec![1, 2], space.layer_sizes());
+        let graph = space.graph();
+        assert_eq!(1, graph.diameter());
+        let mut out = Vec::new();
+        graph.write_dot(&mut out, |r| format!("{r:?}")).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.starts_with("digraph {\n"));
+        assert_eq!(3, dot.matches("[label=").count());
+        assert_eq!(6, dot.matches(" -> ").count());
+    }
+}
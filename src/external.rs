@@ -0,0 +1,363 @@
+use core::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Goal, State, Undirected};
+
+/// A `State` with a fixed-size binary encoding, so that layers can be
+/// written to disk and sorted as raw records.
+pub trait Packed: State + Sized {
+    const SIZE: usize;
+
+    fn pack(&self, out: &mut [u8]);
+    fn unpack(bytes: &[u8]) -> Self;
+}
+
+struct Records {
+    reader: BufReader<File>,
+    size: usize,
+}
+
+impl Records {
+    fn open(path: &Path, size: usize) -> io::Result<Records> {
+        Ok(Records {
+            reader: BufReader::new(File::open(path)?),
+            size,
+        })
+    }
+
+    fn next(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut record = vec![0; self.size];
+        match self.reader.read_exact(&mut record) {
+            Ok(()) => Ok(Some(record)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Breadth-first search that keeps each depth layer in a sorted file of
+/// packed states. Successors are sorted in memory in chunks, the chunks are
+/// merged, and states already present in earlier layers are removed during
+/// the merge (delayed duplicate detection). The path is recovered by a
+/// backward pass over the stored layers.
+pub struct ExternalBfs<T: Packed, G: Goal<T> = fn(&T) -> bool> {
+    start: Rc<T>,
+    dir: PathBuf,
+    // Directory of the current run inside `dir`.
+    work: PathBuf,
+    chunk: usize,
+    undirected: bool,
+    goal: G,
+}
+
+impl<T: Packed> ExternalBfs<T> {
+    /// Each run keeps its files in a new directory inside `dir`, which must
+    /// exist, and removes that directory once the search ends.
+    pub fn new<P: Into<PathBuf>>(start: Rc<T>, dir: P) -> ExternalBfs<T> {
+        ExternalBfs {
+            start,
+            dir: dir.into(),
+            work: PathBuf::new(),
+            chunk: 1 << 20,
+            undirected: false,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+        }
+    }
+}
+
+impl<T: Packed, G: Goal<T>> ExternalBfs<T, G> {
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> ExternalBfs<T, H> {
+        ExternalBfs {
+            start: self.start,
+            dir: self.dir,
+            work: self.work,
+            chunk: self.chunk,
+            undirected: self.undirected,
+            goal,
+        }
+    }
+
+    /// Maximum number of successors sorted in memory at once.
+    pub fn with_chunk_size(mut self, states: usize) -> ExternalBfs<T, G> {
+        self.chunk = states.max(1);
+        self
+    }
+
+    // Creates an empty directory inside `dir` that no other search uses.
+    fn create_work_dir(&self) -> io::Result<PathBuf> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        loop {
+            let index = NEXT.fetch_add(1, Ordering::Relaxed);
+            let path = self.dir.join(format!("arbor-{}-{index}", process::id()));
+            match fs::create_dir(&path) {
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                result => return result.map(|()| path),
+            }
+        }
+    }
+
+    fn layer_path(&self, depth: usize) -> PathBuf {
+        self.work.join(format!("layer-{depth}"))
+    }
+
+    fn run_path(&self, index: usize) -> PathBuf {
+        self.work.join(format!("run-{index}"))
+    }
+
+    fn write_run(&self, index: usize, buffer: &mut Vec<u8>) -> io::Result<()> {
+        let mut records: Vec<&[u8]> = buffer.chunks_exact(T::SIZE).collect();
+        records.sort_unstable();
+        records.dedup();
+        let mut out = BufWriter::new(File::create(self.run_path(index))?);
+        for record in records {
+            out.write_all(record)?;
+        }
+        out.flush()?;
+        buffer.clear();
+        Ok(())
+    }
+
+    // Expands layer `depth` into sorted runs of successors and returns how
+    // many runs were written.
+    fn expand(&self, depth: usize) -> io::Result<usize> {
+        let mut layer = Records::open(&self.layer_path(depth), T::SIZE)?;
+        let mut buffer = Vec::with_capacity(self.chunk.min(1 << 16) * T::SIZE);
+        let mut successors = Vec::new();
+        let mut runs = 0;
+        while let Some(record) = layer.next()? {
+            T::unpack(&record).neighbors_into(&mut successors);
+            for t in successors.drain(..) {
+                let start = buffer.len();
+                buffer.resize(start + T::SIZE, 0);
+                t.pack(&mut buffer[start..]);
+                if buffer.len() >= self.chunk * T::SIZE {
+                    self.write_run(runs, &mut buffer)?;
+                    runs += 1;
+                }
+            }
+        }
+        if !buffer.is_empty() {
+            self.write_run(runs, &mut buffer)?;
+            runs += 1;
+        }
+        Ok(runs)
+    }
+
+    // Merges the runs into layer `depth + 1`, dropping states seen in earlier
+    // layers, and returns the first goal in the new layer if any.
+    fn merge(&self, runs: usize, depth: usize) -> io::Result<(usize, Option<Rc<T>>)> {
+        let mut sources = Vec::new();
+        let mut heap = BinaryHeap::new();
+        for i in 0..runs {
+            let mut run = Records::open(&self.run_path(i), T::SIZE)?;
+            if let Some(record) = run.next()? {
+                heap.push(Reverse((record, i)));
+            }
+            sources.push(run);
+        }
+        let oldest = if self.undirected {
+            depth.saturating_sub(1)
+        } else {
+            0
+        };
+        let mut seen = Vec::new();
+        for d in oldest..=depth {
+            let mut layer = Records::open(&self.layer_path(d), T::SIZE)?;
+            let head = layer.next()?;
+            seen.push((layer, head));
+        }
+        let mut out = BufWriter::new(File::create(self.layer_path(depth + 1))?);
+        let mut last: Option<Vec<u8>> = None;
+        let mut count = 0;
+        let mut goal = None;
+        while let Some(Reverse((record, i))) = heap.pop() {
+            if let Some(next) = sources[i].next()? {
+                heap.push(Reverse((next, i)));
+            }
+            if last.as_ref() == Some(&record) {
+                continue;
+            }
+            let mut duplicate = false;
+            for (layer, head) in seen.iter_mut() {
+                while head.as_ref().is_some_and(|h| *h < record) {
+                    *head = layer.next()?;
+                }
+                duplicate |= head.as_ref() == Some(&record);
+            }
+            if !duplicate {
+                out.write_all(&record)?;
+                count += 1;
+                if goal.is_none() {
+                    let state = T::unpack(&record);
+                    if self.goal.is_goal(&state) {
+                        goal = Some(Rc::new(state));
+                    }
+                }
+            }
+            last = Some(record);
+        }
+        out.flush()?;
+        for i in 0..runs {
+            fs::remove_file(self.run_path(i))?;
+        }
+        Ok((count, goal))
+    }
+
+    // Walks back from `goal` at `depth`, finding a parent in each stored
+    // layer.
+    fn path(&self, goal: Rc<T>, depth: usize) -> io::Result<Vec<Rc<T>>> {
+        let mut result = vec![goal];
+        let mut successors = Vec::new();
+        for d in (0..depth).rev() {
+            let child = Rc::clone(result.last().unwrap());
+            let mut layer = Records::open(&self.layer_path(d), T::SIZE)?;
+            loop {
+                let Some(record) = layer.next()? else {
+                    return Err(io::Error::new(ErrorKind::InvalidData, "missing parent"));
+                };
+                let state = T::unpack(&record);
+                state.neighbors_into(&mut successors);
+                let found = successors.contains(&child);
+                successors.clear();
+                if found {
+                    result.push(Rc::new(state));
+                    break;
+                }
+            }
+        }
+        result.reverse();
+        Ok(result)
+    }
+
+    fn search(&self) -> io::Result<Option<Vec<Rc<T>>>> {
+        let mut record = vec![0; T::SIZE];
+        self.start.pack(&mut record);
+        let mut out = File::create(self.layer_path(0))?;
+        out.write_all(&record)?;
+        if self.goal.is_goal(&self.start) {
+            return Ok(Some(vec![Rc::clone(&self.start)]));
+        }
+        let mut depth = 0;
+        loop {
+            let runs = self.expand(depth)?;
+            let (count, goal) = self.merge(runs, depth)?;
+            depth += 1;
+            if let Some(goal) = goal {
+                return self.path(goal, depth).map(Some);
+            }
+            if count == 0 {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Removes its directory and every file in it before returning. If the
+    /// search fails, its error is returned rather than any from the cleanup.
+    pub fn run(&mut self) -> io::Result<Option<Vec<Rc<T>>>> {
+        self.work = self.create_work_dir()?;
+        let result = self.search();
+        let cleanup = fs::remove_dir_all(&self.work);
+        let result = result?;
+        cleanup?;
+        Ok(result)
+    }
+}
+
+impl<T: Packed + Undirected, G: Goal<T>> ExternalBfs<T, G> {
+    /// Since every move can be undone, duplicates can only come from the
+    /// current and previous layer, so older layers are not read again.
+    pub fn undirected(mut self) -> ExternalBfs<T, G> {
+        self.undirected = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Discs};
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arbor-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_hanoi() {
+        let dir = scratch("hanoi");
+        let start = Rc::new(Discs([0u8; 6]));
+        let directed = ExternalBfs::new(Rc::clone(&start), &dir)
+            .with_chunk_size(16)
+            .run();
+        let undirected = ExternalBfs::new(start, &dir)
+            .with_chunk_size(16)
+            .undirected()
+            .run();
+        for path in [directed, undirected] {
+            let path = path.unwrap().unwrap();
+            assert_eq!(64, path.len());
+            assert!(is_path(&path));
+        }
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let dir = scratch("unreachable");
+        let start = Rc::new(Discs([0u8; 3]));
+        let result = ExternalBfs::new(start, &dir)
+            .with_goal(|d: &Discs<3>| d.0 == [0, 0, 3])
+            .run()
+            .unwrap();
+        assert!(result.is_none());
+        fs::remove_dir(dir).unwrap();
+    }
+
+    #[test]
+    fn test_foreign_files() {
+        // Files already in the directory are neither read nor removed.
+        let dir = scratch("foreign");
+        for name in ["layer-0", "run-0"] {
+            fs::write(dir.join(name), "keep").unwrap();
+        }
+        let path = ExternalBfs::new(Rc::new(Discs([0u8; 3])), &dir)
+            .with_chunk_size(2)
+            .run()
+            .unwrap();
+        assert_eq!(8, path.unwrap().len());
+        for name in ["layer-0", "run-0"] {
+            assert_eq!("keep", fs::read_to_string(dir.join(name)).unwrap());
+        }
+        assert_eq!(2, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_on_error() {
+        // The goal is checked while layer 1 is merged; it puts a directory in
+        // the way of layer 2, so the next merge fails after writing its runs.
+        let dir = scratch("error");
+        let root = dir.clone();
+        let obstruct = move |_: &Discs<4>| {
+            for entry in fs::read_dir(&root).unwrap() {
+                fs::create_dir(entry.unwrap().path().join("layer-2")).ok();
+            }
+            false
+        };
+        let result = ExternalBfs::new(Rc::new(Discs([0u8; 4])), &dir)
+            .with_chunk_size(1)
+            .with_goal(obstruct)
+            .run();
+        assert_eq!(ErrorKind::IsADirectory, result.unwrap_err().kind());
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir(dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use ahash::HashMap;
+use std::rc::Rc;
+
+use crate::{ClosedSet, Goal, State, Target};
+
+/// Marks a `State` where every move can be undone by a move back, so that a
+/// state's successors are also its predecessors.
+pub trait Undirected: State {}
+
+// Result of one layered pass: goal depth, the goal reached and, if a midpoint
+// depth was given, the state at that depth on the way to it.
+struct Pass<T> {
+    depth: usize,
+    goal: Rc<T>,
+    midpoint: Option<Rc<T>>,
+}
+
+// Layer-by-layer search from `start` that keeps three layers at a time. If
+// `midpoint` is given, each state remembers the state it passed through at
+// that depth. Layers are expanded in discovery order, so the goal found is
+// the same on every run. Each layer is built by `layer`.
+fn pass<T, H, C>(
+    start: &Rc<T>,
+    goal: &H,
+    midpoint: Option<usize>,
+    peak: &mut usize,
+    layer: &impl Fn() -> C,
+) -> Option<Pass<T>>
+where
+    T: Undirected,
+    H: Goal<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+{
+    let mut previous = layer();
+    let mut current = layer();
+    let mut order = vec![Rc::clone(start)];
+    current.insert(
+        Rc::clone(start),
+        (midpoint == Some(0)).then(|| Rc::clone(start)),
+    );
+    let mut buffer = Vec::new();
+    let mut depth = 0;
+    while !order.is_empty() {
+        if let Some(t) = order.iter().find(|t| goal.is_goal(t)) {
+            return Some(Pass {
+                depth,
+                goal: Rc::clone(t),
+                midpoint: current.get(t).unwrap().clone(),
+            });
+        }
+        let mut next = layer();
+        let mut next_order = Vec::new();
+        for state in order.iter() {
+            let mid = current.get(state).unwrap();
+            state.neighbors_into(&mut buffer);
+            for t in buffer.drain(..) {
+                if previous.contains(&t) || current.contains(&t) || next.contains(&t) {
+                    continue;
+                }
+                let mid = if midpoint == Some(depth + 1) {
+                    Some(Rc::clone(&t))
+                } else {
+                    mid.clone()
+                };
+                next.insert(Rc::clone(&t), mid);
+                next_order.push(t);
+            }
+        }
+        *peak = (*peak).max(previous.len() + current.len() + next.len());
+        previous = core::mem::replace(&mut current, next);
+        order = next_order;
+        depth += 1;
+    }
+    None
+}
+
+/// Breadth-first search that keeps only the previous, current and next
+/// layers. This is enough to detect duplicates because in an `Undirected`
+/// state space no move leads back further than the previous layer.
+///
+/// Finding the goal depth takes one pass. Recovering the path records, for
+/// every state beyond the middle layer, the state it passed through there,
+/// then solves both halves the same way. Each layer is kept in a `C` built by
+/// `F`, which can be replaced with `with_closed_set`.
+pub struct FrontierSearch<
+    T: Undirected,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>> = HashMap<Rc<T>, Option<Rc<T>>>,
+    F: Fn() -> C = fn() -> C,
+> {
+    start: Rc<T>,
+    goal: G,
+    peak: usize,
+    layer: F,
+}
+
+impl<T: Undirected> FrontierSearch<T> {
+    pub fn new(start: Rc<T>) -> FrontierSearch<T> {
+        FrontierSearch {
+            start,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            peak: 0,
+            layer: HashMap::default as fn() -> HashMap<Rc<T>, Option<Rc<T>>>,
+        }
+    }
+}
+
+impl<T, G, C, F> FrontierSearch<T, G, C, F>
+where
+    T: Undirected,
+    G: Goal<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    F: Fn() -> C,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> FrontierSearch<T, H, C, F> {
+        FrontierSearch {
+            start: self.start,
+            goal,
+            peak: self.peak,
+            layer: self.layer,
+        }
+    }
+
+    /// Calls `layer` for each layer of a pass, three of which are held at
+    /// once.
+    pub fn with_closed_set<D, E>(self, layer: E) -> FrontierSearch<T, G, D, E>
+    where
+        D: ClosedSet<Rc<T>, Option<Rc<T>>>,
+        E: Fn() -> D,
+    {
+        FrontierSearch {
+            start: self.start,
+            goal: self.goal,
+            peak: self.peak,
+            layer,
+        }
+    }
+
+    /// Most states held at once by any pass so far.
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    // Shortest path from `start` to `goal`, which lie `depth` moves apart.
+    fn solve(&mut self, start: Rc<T>, goal: Rc<T>, depth: usize) -> Vec<Rc<T>> {
+        if depth == 0 {
+            return vec![start];
+        }
+        if depth == 1 {
+            return vec![start, goal];
+        }
+        let half = depth / 2;
+        let target = Target(Rc::clone(&goal));
+        let found = pass(&start, &target, Some(half), &mut self.peak, &self.layer).unwrap();
+        let midpoint = found.midpoint.unwrap();
+        let mut result = self.solve(start, Rc::clone(&midpoint), half);
+        result.pop();
+        result.extend(self.solve(midpoint, goal, depth - half));
+        result
+    }
+
+    /// Number of moves to the nearest goal, found in a single pass.
+    pub fn goal_depth(&mut self) -> Option<usize> {
+        pass(&self.start, &self.goal, None, &mut self.peak, &self.layer).map(|p| p.depth)
+    }
+
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        let found = pass(&self.start, &self.goal, None, &mut self.peak, &self.layer)?;
+        Some(self.solve(Rc::clone(&self.start), found.goal, found.depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Towers};
+    use crate::{reachable, Tree};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_hanoi() {
+        for d in 1..7 {
+            let start = Rc::new(Towers::new(3, d));
+            let mut search = FrontierSearch::new(Rc::clone(&start));
+            assert_eq!(Some(2usize.pow(d as u32) - 1), search.goal_depth());
+            let path = search.run().unwrap();
+            assert_eq!(2usize.pow(d as u32), path.len());
+            assert_eq!(start, path[0]);
+            assert!(State::is_goal(&**path.last().unwrap()));
+            assert!(is_path(&path));
+        }
+    }
+
+    #[test]
+    fn test_against_tree() {
+        let start = Rc::new(Towers::new(4, 4));
+        let cleared = |t: &Towers| t.pegs[0].is_empty() && t.pegs[1].is_empty();
+        let expected = Tree::new(Rc::clone(&start))
+            .with_goal(cleared)
+            .run()
+            .unwrap();
+        let mut search = FrontierSearch::new(Rc::clone(&start)).with_goal(cleared);
+        let path = search.run().unwrap();
+        assert_eq!(expected.len(), path.len());
+        assert!(cleared(path.last().unwrap()));
+        assert!(search.peak() < reachable(start).len());
+        let mut ordered = search.with_closed_set(BTreeMap::new);
+        assert_eq!(Some(path), ordered.run());
+    }
+}
@@ -0,0 +1,115 @@
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+use crate::astar::Heuristic;
+use crate::weighted::{Cost, WeightedState};
+use crate::Goal;
+
+enum Iteration<T, C> {
+    Found(Vec<Rc<T>>, C),
+    Exceeded(C),
+    Exhausted,
+}
+
+pub struct IdaStar<T: Heuristic, G: Goal<T> = fn(&T) -> bool> {
+    start: Rc<T>,
+    thresholds: Vec<T::Cost>,
+    goal: G,
+}
+
+impl<T: Heuristic> IdaStar<T> {
+    pub fn new(start: Rc<T>) -> IdaStar<T> {
+        IdaStar {
+            start,
+            thresholds: Vec::new(),
+            goal: <T as WeightedState>::is_goal as fn(&T) -> bool,
+        }
+    }
+}
+
+impl<T: Heuristic, G: Goal<T>> IdaStar<T, G> {
+    /// The heuristic must still be admissible for the new goal for the
+    /// returned path to be optimal.
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> IdaStar<T, H> {
+        IdaStar {
+            start: self.start,
+            thresholds: self.thresholds,
+            goal,
+        }
+    }
+
+    /// The f-cost bound of every iteration run so far, in order.
+    pub fn thresholds(&self) -> &[T::Cost] {
+        &self.thresholds
+    }
+
+    fn bounded(&self, bound: T::Cost) -> Iteration<T, T::Cost> {
+        if self.goal.is_goal(&self.start) {
+            return Iteration::Found(vec![Rc::clone(&self.start)], T::Cost::zero());
+        }
+        let mut exceeded: Option<T::Cost> = None;
+        let mut path = vec![Rc::clone(&self.start)];
+        let mut costs = vec![T::Cost::zero()];
+        let mut stack: Vec<IntoIter<(Rc<T>, T::Cost)>> = vec![self.start.neighbors().into_iter()];
+        while let Some(successors) = stack.last_mut() {
+            let Some((next, step)) = successors.next() else {
+                stack.pop();
+                path.pop();
+                costs.pop();
+                continue;
+            };
+            if path.contains(&next) {
+                continue;
+            }
+            let g = *costs.last().unwrap() + step;
+            let f = g + next.heuristic();
+            if f > bound {
+                exceeded = Some(exceeded.map_or(f, |e| e.min(f)));
+                continue;
+            }
+            if self.goal.is_goal(&next) {
+                path.push(next);
+                return Iteration::Found(path, g);
+            }
+            stack.push(next.neighbors().into_iter());
+            path.push(next);
+            costs.push(g);
+        }
+        match exceeded {
+            Some(next) => Iteration::Exceeded(next),
+            None => Iteration::Exhausted,
+        }
+    }
+
+    pub fn run(&mut self) -> Option<(Vec<Rc<T>>, T::Cost)> {
+        let mut bound = self.start.heuristic();
+        loop {
+            self.thresholds.push(bound);
+            match self.bounded(bound) {
+                Iteration::Found(path, cost) => return Some((path, cost)),
+                Iteration::Exceeded(next) => bound = next,
+                Iteration::Exhausted => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+
+    #[test]
+    fn test_hanoi() {
+        for d in 1..4 {
+            let mut search = IdaStar::new(Rc::new(Towers::new(3, d)));
+            let (path, cost) = search.run().unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, cost);
+            assert_eq!(cost, path.len() - 1);
+            let thresholds = search.thresholds();
+            assert_eq!(d, thresholds[0]);
+            assert_eq!(cost, *thresholds.last().unwrap());
+            assert!(thresholds.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+}
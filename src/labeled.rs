@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use crate::{ClosedSet, Goal, Observer, OpenList, State, Tree};
+
+/// A `State` whose moves carry a label, such as "move a disc from peg 0 to
+/// peg 2". `labeled_neighbors` must yield the same successors as `neighbors`;
+/// implementing `neighbors` with `strip_labels` keeps the two in step.
+pub trait Labeled: State {
+    type Action;
+
+    fn labeled_neighbors(&self) -> Vec<(Self::Action, Rc<Self>)>;
+}
+
+/// The successors from `labeled_neighbors` without their actions, for use as
+/// `State::neighbors` so that moves are only written once.
+pub fn strip_labels<T: Labeled>(state: &T) -> Vec<Rc<T>> {
+    state
+        .labeled_neighbors()
+        .into_iter()
+        .map(|(_, t)| t)
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct LabeledPath<T: Labeled> {
+    pub states: Vec<Rc<T>>,
+    /// `actions[i]` leads from `states[i]` to `states[i + 1]`.
+    pub actions: Vec<T::Action>,
+}
+
+impl<T: Labeled> LabeledPath<T> {
+    /// Recovers the actions along a path returned by any search driver by
+    /// expanding each state again, so no driver has to store them. Where
+    /// several actions lead to the same state, the first one is used.
+    ///
+    /// Returns `None` if some consecutive states are not connected by a
+    /// labeled move, e.g. when `labeled_neighbors` disagrees with `neighbors`.
+    pub fn new(states: Vec<Rc<T>>) -> Option<LabeledPath<T>> {
+        let actions = states
+            .windows(2)
+            .map(|w| {
+                w[0].labeled_neighbors()
+                    .into_iter()
+                    .find(|(_, t)| *t == w[1])
+                    .map(|(action, _)| action)
+            })
+            .collect::<Option<_>>()?;
+        Some(LabeledPath { states, actions })
+    }
+}
+
+impl<T, G, O, C, Q> Tree<T, G, O, C, Q>
+where
+    T: Labeled,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<(Rc<T>, Option<Rc<T>>, usize)>,
+{
+    /// `run`, with the actions along the path. `None` also covers a path
+    /// whose actions cannot be recovered; see `LabeledPath::new`.
+    pub fn run_labeled(&mut self) -> Option<LabeledPath<T>> {
+        self.run().and_then(LabeledPath::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+
+    #[test]
+    fn test_hanoi_actions() {
+        let path = Tree::new(Rc::new(Towers::new(3, 1))).run_labeled().unwrap();
+        assert_eq!(vec![(0, 2)], path.actions);
+
+        for d in 2..6 {
+            let path = Tree::new(Rc::new(Towers::new(3, d))).run_labeled().unwrap();
+            assert_eq!(path.states.len() - 1, path.actions.len());
+            for (i, &(from, to)) in path.actions.iter().enumerate() {
+                let next = path.states[i].move_disc(from, to).unwrap();
+                assert_eq!(*path.states[i + 1], next);
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_neighbors() {
+        let start = Rc::new(Towers::new(3, 2));
+        let far = Rc::new(
+            Towers::new(3, 2)
+                .move_disc(0, 1)
+                .unwrap()
+                .move_disc(0, 2)
+                .unwrap(),
+        );
+        assert!(LabeledPath::new(vec![start, far]).is_none());
+    }
+}
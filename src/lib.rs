@@ -3,120 +3,459 @@ use core::hash::Hash;
 use std::collections::VecDeque;
 use std::rc::Rc;
 
+mod all_paths;
+mod arena;
+mod astar;
+mod backend;
+mod bidirectional;
+mod bitstate;
+#[cfg(feature = "serde")]
+mod checkpoint;
+mod compact;
+mod depth_first;
+mod explore;
+mod external;
+mod frontier;
+mod ida_star;
+mod labeled;
+mod limits;
+mod owned;
+mod stats;
+mod symmetry;
+pub mod sync;
+#[cfg(test)]
+mod testing;
+mod weighted;
+
+pub use all_paths::{AllShortestPaths, Paths, ShortestPaths};
+pub use arena::Arena;
+pub use astar::{AStar, Heuristic};
+pub use backend::{ClosedSet, OpenList};
+pub use bidirectional::{Bidirectional, Reversible};
+pub use bitstate::BitState;
+#[cfg(feature = "serde")]
+pub use checkpoint::Checkpoint;
+pub use compact::CompactTree;
+pub use depth_first::{DepthFirst, IterativeDeepening};
+pub use explore::{reachable, reachable_in, Reachable, StateGraph};
+pub use external::{ExternalBfs, Packed};
+pub use frontier::{FrontierSearch, Undirected};
+pub use ida_star::IdaStar;
+pub use labeled::{strip_labels, Labeled, LabeledPath};
+pub use limits::{Limit, Limits, SearchResult};
+pub use owned::{OwnedState, OwnedTree};
+pub use stats::{LayerStats, Observer, SearchStats};
+pub use symmetry::{Canonical, SymmetricTree};
+pub use weighted::{Cost, Dijkstra, Entry, WeightedState};
+
 pub trait State: Hash + PartialEq + Eq {
     fn neighbors(&self) -> Vec<Rc<Self>>;
     fn is_goal(&self) -> bool;
+
+    /// Appends the successors of `self` to `out`. Breadth-first drivers call
+    /// this with a buffer they reuse across expansions; override it to stream
+    /// successors without allocating a `Vec` per state.
+    fn neighbors_into(&self, out: &mut Vec<Rc<Self>>) {
+        out.extend(self.neighbors());
+    }
+}
+
+/// A goal predicate kept apart from the state type, so that one state space
+/// can be searched for different targets. Search drivers default to the
+/// state's own `is_goal`.
+pub trait Goal<T: ?Sized> {
+    fn is_goal(&self, state: &T) -> bool;
+}
+
+impl<T: ?Sized, F: Fn(&T) -> bool> Goal<T> for F {
+    fn is_goal(&self, state: &T) -> bool {
+        self(state)
+    }
+}
+
+/// Matches exactly one concrete state.
+pub struct Target<T>(pub Rc<T>);
+
+impl<T: PartialEq> Goal<T> for Target<T> {
+    fn is_goal(&self, state: &T) -> bool {
+        *self.0 == *state
+    }
 }
 
-pub struct Tree<T: State> {
-    queue: VecDeque<(Rc<T>, Rc<T>)>,
-    visited: HashMap<Rc<T>, Option<Rc<T>>>,
+// A queued state, the state it was reached from and its depth.
+type Queued<T> = (Rc<T>, Option<Rc<T>>, usize);
+
+/// Breadth-first search. The closed set `C` and open list `Q` default to an
+/// `ahash` map and a `VecDeque`; see `with_closed_set` and `with_open_list`.
+pub struct Tree<
+    T: State,
+    G: Goal<T> = fn(&T) -> bool,
+    O: Observer<T> = (),
+    C: ClosedSet<Rc<T>, Option<Rc<T>>> = HashMap<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>> = VecDeque<Queued<T>>,
+> {
+    queue: Q,
+    // A state popped while a limit was exceeded, to be expanded first.
+    held: Option<Queued<T>>,
+    visited: C,
+    goal: G,
+    limits: Limits,
+    stats: SearchStats,
+    // States at `max_depth` whose successors were dropped, with their depth.
+    pruned: Vec<(Rc<T>, usize)>,
+    observer: O,
+    // Number of layers reported to `on_depth_complete` so far.
+    completed: usize,
+    buffer: Vec<Rc<T>>,
 }
 
 impl<T: State> Tree<T> {
     pub fn new(start: Rc<T>) -> Tree<T> {
+        Tree::from_roots([start])
+    }
+
+    /// Searches from several start states at once. Each returned path begins
+    /// at the root it was reached from.
+    pub fn from_roots<I: IntoIterator<Item = Rc<T>>>(roots: I) -> Tree<T> {
         let mut tree = Tree {
             queue: VecDeque::new(),
+            held: None,
             visited: HashMap::new(),
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            limits: Limits::default(),
+            stats: SearchStats::default(),
+            pruned: Vec::new(),
+            observer: (),
+            completed: 0,
+            buffer: Vec::new(),
         };
-        tree.visited.insert(Rc::clone(&start), None);
-        for t in start.neighbors() {
-            tree.queue.push_back((t, Rc::clone(&start)));
+        for root in roots {
+            tree.queue.push((root, None, 0));
         }
         tree
     }
+}
+
+impl<T, G, O, C, Q> Tree<T, G, O, C, Q>
+where
+    T: State,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>>,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> Tree<T, H, O, C, Q> {
+        Tree {
+            queue: self.queue,
+            held: self.held,
+            visited: self.visited,
+            goal,
+            limits: self.limits,
+            stats: self.stats,
+            pruned: self.pruned,
+            observer: self.observer,
+            completed: self.completed,
+            buffer: self.buffer,
+        }
+    }
+
+    pub fn with_observer<P: Observer<T>>(self, observer: P) -> Tree<T, G, P, C, Q> {
+        Tree {
+            queue: self.queue,
+            held: self.held,
+            visited: self.visited,
+            goal: self.goal,
+            limits: self.limits,
+            stats: self.stats,
+            pruned: self.pruned,
+            observer,
+            completed: self.completed,
+            buffer: self.buffer,
+        }
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// States cut off by an earlier `max_depth` that the new limits allow
+    /// deeper are given their successors, so the search continues past them.
+    pub fn with_limits(mut self, limits: Limits) -> Tree<T, G, O, C, Q> {
+        self.limits = limits;
+        let (released, kept) = core::mem::take(&mut self.pruned)
+            .into_iter()
+            .partition(|(_, depth)| self.limits.max_depth.is_none_or(|m| *depth < m));
+        self.pruned = kept;
+        for (current, depth) in released {
+            self.push_successors(&current, depth);
+        }
+        self
+    }
+
+    /// Stores expanded states in `closed` from now on, moving over any
+    /// already there.
+    pub fn with_closed_set<D>(self, mut closed: D) -> Tree<T, G, O, D, Q>
+    where
+        D: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    {
+        for (t, prev) in self.visited.iter() {
+            closed.insert(Rc::clone(t), prev.clone());
+        }
+        Tree {
+            queue: self.queue,
+            held: self.held,
+            visited: closed,
+            goal: self.goal,
+            limits: self.limits,
+            stats: self.stats,
+            pruned: self.pruned,
+            observer: self.observer,
+            completed: self.completed,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Queues states in `open` from now on, moving over any already queued.
+    /// It must pop first-in first-out for the search to be breadth-first.
+    pub fn with_open_list<R: OpenList<Queued<T>>>(mut self, mut open: R) -> Tree<T, G, O, C, R> {
+        while let Some(entry) = self.queue.pop() {
+            open.push(entry);
+        }
+        Tree {
+            queue: open,
+            held: self.held,
+            visited: self.visited,
+            goal: self.goal,
+            limits: self.limits,
+            stats: self.stats,
+            pruned: self.pruned,
+            observer: self.observer,
+            completed: self.completed,
+            buffer: self.buffer,
+        }
+    }
+
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
 
     fn get_path_to_node(&self, node: &Rc<T>) -> Vec<Rc<T>> {
-        let mut result = Vec::new();
-        let mut current = node;
-        result.push(Rc::clone(current));
-        while let Some(c) = self.visited.get(current) {
-            if let Some(d) = c {
-                current = d;
-                result.push(Rc::clone(current));
-            } else {
-                break;
-            }
+        path_to(&self.visited, node)
+    }
+
+    // Reports every layer shallower than `depth` as complete.
+    fn complete_layers(&mut self, depth: usize) {
+        while self.completed < depth.min(self.stats.layers.len()) {
+            self.observer.on_depth_complete(self.completed, &self.stats);
+            self.completed += 1;
         }
-        result.reverse();
-        result
     }
 
-    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
-        while let Some((current, prev)) = self.queue.pop_front() {
-            if self.visited.contains_key(&current) {
+    // Queues the successors of `current` and counts them as generated.
+    fn push_successors(&mut self, current: &Rc<T>, depth: usize) {
+        current.neighbors_into(&mut self.buffer);
+        let generated = self.buffer.len();
+        for t in self.buffer.drain(..) {
+            self.queue.push((t, Some(Rc::clone(current)), depth + 1));
+        }
+        self.stats.generated += generated;
+        self.stats.layers[depth].generated += generated;
+        self.stats.peak_frontier = self.stats.peak_frontier.max(self.queue.len());
+    }
+
+    /// Pops states until one that has not been seen before and expands it.
+    /// A state that would exceed a limit is put back, so the search can be
+    /// continued with larger limits.
+    pub fn step(&mut self) -> Step<T> {
+        while let Some((current, prev, depth)) = self.held.take().or_else(|| self.queue.pop()) {
+            if self.visited.contains(&current) {
+                self.stats.duplicates += 1;
+                self.observer.on_duplicate(&current);
                 continue;
             }
-            self.visited
-                .insert(Rc::clone(&current), Some(Rc::clone(&prev)));
-            if current.is_goal() {
-                return Some(self.get_path_to_node(&current));
+            if let Some(which) = self
+                .limits
+                .exceeded(self.stats.expanded, self.visited.len())
+            {
+                self.held = Some((current, prev, depth));
+                return Step::LimitReached(which);
             }
-            for t in current.neighbors() {
-                self.queue.push_back((t, Rc::clone(&current)));
+            self.complete_layers(depth);
+            self.visited.insert(Rc::clone(&current), prev);
+            self.observer.on_expand(&current, depth);
+            if self.stats.layers.len() <= depth {
+                self.stats.layers.resize(depth + 1, LayerStats::default());
             }
+            if self.limits.max_depth.is_some_and(|m| depth >= m) {
+                self.pruned.push((Rc::clone(&current), depth));
+            } else {
+                self.push_successors(&current, depth);
+            }
+            self.stats.expanded += 1;
+            self.stats.visited = self.visited.len();
+            self.stats.depth = depth;
+            self.stats.layers[depth].expanded += 1;
+            if self.goal.is_goal(&current) {
+                let path = self.get_path_to_node(&current);
+                self.observer.on_goal(&path);
+                return Step::Goal(path);
+            }
+            return Step::Expanded(current);
+        }
+        self.complete_layers(usize::MAX);
+        if !self.pruned.is_empty() {
+            return Step::LimitReached(Limit::Depth);
         }
-        None
+        Step::Exhausted
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[derive(Hash, Clone, PartialEq, Eq, Debug)]
-    struct Towers {
-        pegs: Vec<Vec<usize>>,
+    /// Like `run`, but tells an unreachable goal apart from one that was not
+    /// reached within the limits.
+    pub fn search(&mut self) -> SearchResult<T> {
+        loop {
+            match self.step() {
+                Step::Expanded(_) => {}
+                Step::Goal(path) => return SearchResult::Found(path),
+                Step::Exhausted => return SearchResult::Exhausted,
+                Step::LimitReached(which) => {
+                    return SearchResult::LimitReached {
+                        which,
+                        stats: self.stats.clone(),
+                    }
+                }
+            }
+        }
     }
 
-    impl Towers {
-        fn new(pegs: usize, discs: usize) -> Towers {
-            let mut result = Towers { pegs: Vec::new() };
-            result.pegs.push((0..discs).collect::<Vec<usize>>());
-            for _ in 1..pegs {
-                result.pegs.push(Vec::new());
+    /// Returns the path to the next goal in breadth-first order. Goals are
+    /// expanded like any other state, so calling `run` again continues the
+    /// search towards the next goal.
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        loop {
+            match self.step() {
+                Step::Expanded(_) => {}
+                Step::Goal(path) => return Some(path),
+                Step::Exhausted | Step::LimitReached(_) => return None,
             }
-            result
         }
+    }
 
-        fn move_disc(&self, from: usize, to: usize) -> Option<Towers> {
-            if from == to
-                || from >= self.pegs.len()
-                || to >= self.pegs.len()
-                || self.pegs[from].is_empty()
-                || (!self.pegs[to].is_empty()
-                    && self.pegs[from].last().unwrap() < self.pegs[to].last().unwrap())
-            {
-                return None;
-            }
-            let mut result = self.clone();
-            let moved = result.pegs[from].pop().unwrap();
-            result.pegs[to].push(moved);
-            Some(result)
+    /// States queued for expansion, oldest first. States reached more than
+    /// once appear once per discovery until they are popped.
+    pub fn frontier(&self) -> impl Iterator<Item = &Rc<T>> {
+        self.held.iter().chain(self.queue.iter()).map(|(t, _, _)| t)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.held.is_none() && self.queue.is_empty()
+    }
+
+    /// Every state in expansion order, goals included.
+    pub fn expanded(&mut self) -> Expanded<'_, T, G, O, C, Q> {
+        Expanded { tree: self }
+    }
+
+    /// Paths to every reachable goal in order of distance, stopping after
+    /// `limit` goals if given.
+    pub fn goals(&mut self, limit: Option<usize>) -> Goals<'_, T, G, O, C, Q> {
+        Goals { tree: self, limit }
+    }
+}
+
+pub enum Step<T> {
+    Expanded(Rc<T>),
+    Goal(Vec<Rc<T>>),
+    Exhausted,
+    LimitReached(Limit),
+}
+
+pub struct Expanded<
+    'a,
+    T: State,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>>,
+> {
+    tree: &'a mut Tree<T, G, O, C, Q>,
+}
+
+impl<T, G, O, C, Q> Iterator for Expanded<'_, T, G, O, C, Q>
+where
+    T: State,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>>,
+{
+    type Item = Rc<T>;
+
+    fn next(&mut self) -> Option<Rc<T>> {
+        match self.tree.step() {
+            Step::Expanded(t) => Some(t),
+            Step::Goal(path) => path.last().cloned(),
+            Step::Exhausted | Step::LimitReached(_) => None,
         }
     }
+}
 
-    impl State for Towers {
-        fn neighbors(&self) -> Vec<Rc<Towers>> {
-            let mut result = Vec::new();
-            for i in 0..self.pegs.len() {
-                for j in 0..self.pegs.len() {
-                    if let Some(neighbor) = self.move_disc(i, j) {
-                        result.push(Rc::new(neighbor));
-                    }
-                }
+pub struct Goals<
+    'a,
+    T: State,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>>,
+> {
+    tree: &'a mut Tree<T, G, O, C, Q>,
+    limit: Option<usize>,
+}
+
+impl<T, G, O, C, Q> Iterator for Goals<'_, T, G, O, C, Q>
+where
+    T: State,
+    G: Goal<T>,
+    O: Observer<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Queued<T>>,
+{
+    type Item = Vec<Rc<T>>;
+
+    fn next(&mut self) -> Option<Vec<Rc<T>>> {
+        if let Some(limit) = self.limit.as_mut() {
+            if *limit == 0 {
+                return None;
             }
-            result
+            *limit -= 1;
         }
+        self.tree.run()
+    }
+}
 
-        fn is_goal(&self) -> bool {
-            for i in 0..(self.pegs.len() - 1) {
-                if !self.pegs[i].is_empty() {
-                    return false;
-                }
-            }
-            true
+pub(crate) fn path_to<T, C: ClosedSet<Rc<T>, Option<Rc<T>>>>(
+    parents: &C,
+    node: &Rc<T>,
+) -> Vec<Rc<T>> {
+    let mut result = Vec::new();
+    let mut current = node;
+    result.push(Rc::clone(current));
+    while let Some(c) = parents.get(current) {
+        if let Some(d) = c {
+            current = d;
+            result.push(Rc::clone(current));
+        } else {
+            break;
         }
     }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Ring, Towers};
+    use std::time::Instant;
 
     fn hanoi_len(pegs: usize, discs: usize) -> usize {
         let start = Towers::new(pegs, discs);
@@ -134,4 +473,288 @@ mod tests {
             assert_eq!(2usize.pow(d as u32) - 1, moves);
         }
     }
+
+    fn solved_towers(discs: usize) -> Rc<Towers> {
+        let mut towers = Towers::new(3, 0);
+        towers.pegs[2] = (0..discs).collect();
+        Rc::new(towers)
+    }
+
+    #[test]
+    fn test_solved_start() {
+        for d in 0..4 {
+            let start = solved_towers(d);
+            let expected = vec![Rc::clone(&start)];
+            let tree = Tree::new(Rc::clone(&start)).run();
+            assert_eq!(Some(&expected), tree.as_ref());
+            let dijkstra = Dijkstra::new(Rc::clone(&start)).run();
+            assert_eq!(Some((expected.clone(), 0)), dijkstra);
+            let astar = AStar::new(Rc::clone(&start)).run();
+            assert_eq!(Some((expected.clone(), 0)), astar);
+            let ida_star = IdaStar::new(Rc::clone(&start)).run();
+            assert_eq!(Some((expected.clone(), 0)), ida_star);
+            let dfs = DepthFirst::new(Rc::clone(&start)).run();
+            assert_eq!(Some(&expected), dfs.as_ref());
+            let iddfs = IterativeDeepening::new(Rc::clone(&start)).run();
+            assert_eq!(Some(&expected), iddfs.as_ref());
+            let bidirectional = Bidirectional::new(Rc::clone(&start), Rc::clone(&start)).run();
+            assert_eq!(Some(&expected), bidirectional.as_ref());
+            let all = AllShortestPaths::new(Rc::clone(&start)).run().unwrap();
+            assert_eq!(0, all.depth());
+            assert_eq!(vec![expected], all.paths().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_no_goal() {
+        let start = Rc::new(Ring::new(5));
+        assert!(Tree::new(Rc::clone(&start)).run().is_none());
+        assert!(Dijkstra::new(Rc::clone(&start)).run().is_none());
+        assert!(AStar::new(Rc::clone(&start)).run().is_none());
+        assert!(IdaStar::new(Rc::clone(&start)).run().is_none());
+        assert!(DepthFirst::new(Rc::clone(&start)).run().is_none());
+        assert!(IterativeDeepening::new(Rc::clone(&start)).run().is_none());
+        assert!(AllShortestPaths::new(Rc::clone(&start)).run().is_none());
+        let other = Rc::new(Ring::new(6));
+        assert!(Bidirectional::new(start, other).run().is_none());
+    }
+
+    #[test]
+    fn test_goal_closure() {
+        for d in 1..5 {
+            let start = Rc::new(Towers::new(3, d));
+            let middle = |t: &Towers| t.pegs[1].len() == d;
+            let path = Tree::new(Rc::clone(&start)).with_goal(middle).run();
+            assert_eq!(2usize.pow(d as u32), path.unwrap().len());
+            let path = DepthFirst::new(Rc::clone(&start)).with_goal(middle).run();
+            assert!(middle(path.unwrap().last().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_goal_target() {
+        let start = Rc::new(Towers::new(3, 3));
+        let target = Rc::new(Towers {
+            pegs: vec![vec![0, 1], vec![], vec![2]],
+        });
+        let path = Tree::new(Rc::clone(&start))
+            .with_goal(Target(Rc::clone(&target)))
+            .run()
+            .unwrap();
+        assert_eq!(vec![start.clone(), target.clone()], path);
+        let (path, cost) = Dijkstra::new(Rc::clone(&start))
+            .with_goal(Target(Rc::clone(&target)))
+            .run()
+            .unwrap();
+        assert_eq!((2, 1), (path.len(), cost));
+        let all = AllShortestPaths::new(start)
+            .with_goal(Target(target))
+            .run()
+            .unwrap();
+        assert_eq!(1, all.count());
+    }
+
+    #[test]
+    fn test_all_goals() {
+        let start = Rc::new(Towers::new(3, 3));
+        let cleared = |t: &Towers| t.pegs[0].is_empty();
+        let mut tree = Tree::new(Rc::clone(&start)).with_goal(cleared);
+        let paths: Vec<Vec<Rc<Towers>>> = tree.goals(None).collect();
+        assert_eq!(8, paths.len());
+        assert!(paths.windows(2).all(|w| w[0].len() <= w[1].len()));
+        for path in &paths {
+            assert_eq!(start, path[0]);
+            assert!(cleared(path.last().unwrap()));
+        }
+        assert!(tree.run().is_none());
+
+        let mut tree = Tree::new(start).with_goal(cleared);
+        assert_eq!(3, tree.goals(Some(3)).count());
+        assert_eq!(5, tree.goals(None).count());
+    }
+
+    #[test]
+    fn test_from_roots() {
+        let far = Rc::new(Towers::new(3, 3));
+        let near = Rc::new(Towers {
+            pegs: vec![vec![], vec![2], vec![0, 1]],
+        });
+        let path = Tree::from_roots([Rc::clone(&far), Rc::clone(&near)])
+            .run()
+            .unwrap();
+        assert_eq!(near, path[0]);
+        assert_eq!(2, path.len());
+
+        let path = Tree::from_roots([far, near]).run().unwrap();
+        assert_eq!(2, path.len());
+
+        assert!(Tree::<Towers>::from_roots([]).run().is_none());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut tree = Tree::new(Rc::new(Towers::new(3, 3)));
+        assert!(matches!(tree.step(), Step::Expanded(_)));
+        assert_eq!(2, tree.frontier().count());
+        let expanded: Vec<Rc<Towers>> = tree.expanded().take(5).collect();
+        assert_eq!(5, expanded.len());
+        assert!(!tree.is_exhausted());
+        let mut steps = 0;
+        let path = loop {
+            steps += 1;
+            match tree.step() {
+                Step::Expanded(t) => assert!(!State::is_goal(&*t)),
+                Step::Goal(path) => break path,
+                Step::Exhausted | Step::LimitReached(_) => panic!("goal not found"),
+            }
+        };
+        assert_eq!(8, path.len());
+        assert_eq!(27, 6 + steps + tree.expanded().count());
+        assert!(tree.is_exhausted());
+    }
+
+    #[test]
+    fn test_limits() {
+        let start = Rc::new(Towers::new(3, 3));
+        let limits = Limits {
+            max_expanded: Some(10),
+            ..Limits::default()
+        };
+        let mut tree = Tree::new(Rc::clone(&start)).with_limits(limits);
+        match tree.search() {
+            SearchResult::LimitReached { which, stats } => {
+                assert_eq!(Limit::Expanded, which);
+                assert_eq!(10, stats.expanded);
+            }
+            _ => panic!("expected the expansion limit"),
+        }
+        let mut tree = tree.with_limits(Limits::default());
+        assert!(matches!(tree.search(), SearchResult::Found(p) if p.len() == 8));
+
+        let limits = Limits {
+            max_depth: Some(6),
+            ..Limits::default()
+        };
+        let mut tree = Tree::new(Rc::clone(&start)).with_limits(limits);
+        assert!(matches!(
+            tree.search(),
+            SearchResult::LimitReached {
+                which: Limit::Depth,
+                ..
+            }
+        ));
+        let mut tree = tree.with_limits(Limits::default());
+        assert!(matches!(tree.search(), SearchResult::Found(p) if p.len() == 8));
+        let mut full = Tree::new(Rc::clone(&start));
+        full.run();
+        assert_eq!(full.stats().expanded, tree.stats().expanded);
+        assert_eq!(full.stats().layers, tree.stats().layers);
+
+        let limits = Limits {
+            max_visited: Some(5),
+            ..Limits::default()
+        };
+        let mut tree = Tree::new(Rc::clone(&start)).with_limits(limits);
+        assert!(matches!(
+            tree.search(),
+            SearchResult::LimitReached {
+                which: Limit::Visited,
+                stats: SearchStats { visited: 5, .. }
+            }
+        ));
+
+        let limits = Limits {
+            deadline: Some(Instant::now()),
+            ..Limits::default()
+        };
+        let mut tree = Tree::new(Rc::clone(&start)).with_limits(limits);
+        assert!(matches!(
+            tree.search(),
+            SearchResult::LimitReached {
+                which: Limit::Deadline,
+                ..
+            }
+        ));
+
+        let mut tree = Tree::new(Rc::new(Ring::new(4)));
+        assert!(matches!(tree.search(), SearchResult::Exhausted));
+        assert_eq!(4, tree.stats().expanded);
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        expanded: usize,
+        duplicates: usize,
+        goals: usize,
+        layers: Vec<usize>,
+    }
+
+    impl Observer<Towers> for Recorder {
+        fn on_expand(&mut self, _state: &Rc<Towers>, _depth: usize) {
+            self.expanded += 1;
+        }
+
+        fn on_duplicate(&mut self, _state: &Rc<Towers>) {
+            self.duplicates += 1;
+        }
+
+        fn on_goal(&mut self, _path: &[Rc<Towers>]) {
+            self.goals += 1;
+        }
+
+        fn on_depth_complete(&mut self, depth: usize, stats: &SearchStats) {
+            assert_eq!(self.layers.len(), depth);
+            self.layers.push(stats.layers[depth].expanded);
+        }
+    }
+
+    #[test]
+    fn test_stats_and_observer() {
+        let start = Rc::new(Towers::new(3, 2));
+        let mut tree = Tree::new(start).with_observer(Recorder::default());
+        assert_eq!(4, tree.run().unwrap().len());
+        assert_eq!(1, tree.observer().goals);
+        assert_eq!(vec![1, 2, 2], tree.observer().layers);
+        while tree.run().is_some() {}
+
+        let stats = tree.stats().clone();
+        let recorder = tree.observer();
+        assert_eq!(9, stats.expanded);
+        assert_eq!(9, stats.visited);
+        assert_eq!(stats.expanded, recorder.expanded);
+        assert_eq!(stats.duplicates, recorder.duplicates);
+        assert_eq!(stats.generated, stats.expanded + stats.duplicates - 1);
+        assert_eq!(stats.layers.len(), recorder.layers.len());
+        assert_eq!(
+            stats.expanded,
+            stats.layers.iter().map(|l| l.expanded).sum::<usize>()
+        );
+        assert_eq!(2.0, stats.layers[0].branching_factor());
+        assert!(stats.peak_frontier > 0);
+    }
+
+    // Only `neighbors_into` is usable, so this checks `Tree` streams through it.
+    #[derive(Hash, PartialEq, Eq, Debug)]
+    struct Streamed(u32);
+
+    impl State for Streamed {
+        fn neighbors(&self) -> Vec<Rc<Streamed>> {
+            unreachable!()
+        }
+
+        fn is_goal(&self) -> bool {
+            self.0 == 10
+        }
+
+        fn neighbors_into(&self, out: &mut Vec<Rc<Streamed>>) {
+            out.extend([self.0 + 1, self.0 * 2].map(|n| Rc::new(Streamed(n))));
+        }
+    }
+
+    #[test]
+    fn test_neighbors_into() {
+        let path = Tree::new(Rc::new(Streamed(1))).run().unwrap();
+        let values: Vec<u32> = path.iter().map(|n| n.0).collect();
+        assert_eq!(vec![1, 2, 4, 5, 10], values);
+    }
 }
@@ -0,0 +1,50 @@
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::SearchStats;
+
+/// Resource budgets for a search. Unset fields are unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    pub max_expanded: Option<usize>,
+    /// States at this depth are still checked against the goal, but their
+    /// successors are held back rather than queued until `Tree::with_limits`
+    /// raises or removes the limit.
+    pub max_depth: Option<usize>,
+    pub max_visited: Option<usize>,
+    pub deadline: Option<Instant>,
+}
+
+impl Limits {
+    // The limit that stops further expansion, checked before each one.
+    pub(crate) fn exceeded(&self, expanded: usize, visited: usize) -> Option<Limit> {
+        if self.max_expanded.is_some_and(|m| expanded >= m) {
+            Some(Limit::Expanded)
+        } else if self.max_visited.is_some_and(|m| visited >= m) {
+            Some(Limit::Visited)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(Limit::Deadline)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Expanded,
+    Depth,
+    Visited,
+    Deadline,
+}
+
+#[derive(Debug)]
+pub enum SearchResult<T> {
+    Found(Vec<Rc<T>>),
+    /// Every reachable state was expanded without reaching a goal.
+    Exhausted,
+    LimitReached {
+        which: Limit,
+        stats: SearchStats,
+    },
+}
@@ -0,0 +1,157 @@
+use ahash::RandomState;
+use core::hash::{BuildHasher, Hash};
+use std::collections::VecDeque;
+
+use crate::arena::Arena;
+use crate::{Goal, OpenList};
+
+/// Like `State`, but successors are produced by value, so small `Copy`
+/// states need no `Rc` allocation.
+pub trait OwnedState: Hash + PartialEq + Eq + Sized {
+    fn neighbors(&self) -> impl Iterator<Item = Self>;
+    fn is_goal(&self) -> bool;
+}
+
+const ROOT: u32 = u32::MAX;
+
+/// Breadth-first search over `OwnedState`. States are interned into an
+/// `Arena` as they are discovered, and both parent links and the queue hold
+/// ids instead of states.
+pub struct OwnedTree<
+    T: OwnedState,
+    G: Goal<T> = fn(&T) -> bool,
+    S: BuildHasher + Default = RandomState,
+    Q: OpenList<u32> = VecDeque<u32>,
+> {
+    arena: Arena<T, S>,
+    parents: Vec<u32>,
+    queue: Q,
+    buffer: Vec<T>,
+    goal: G,
+}
+
+impl<T: OwnedState> OwnedTree<T> {
+    pub fn new(start: T) -> OwnedTree<T> {
+        let mut tree = OwnedTree {
+            arena: Arena::new(),
+            parents: Vec::new(),
+            queue: VecDeque::new(),
+            buffer: Vec::new(),
+            goal: <T as OwnedState>::is_goal as fn(&T) -> bool,
+        };
+        let (id, _) = tree.arena.intern(start);
+        tree.parents.push(ROOT);
+        tree.queue.push_back(id);
+        tree
+    }
+}
+
+impl<T, G, S, Q> OwnedTree<T, G, S, Q>
+where
+    T: OwnedState,
+    G: Goal<T>,
+    S: BuildHasher + Default,
+    Q: OpenList<u32>,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> OwnedTree<T, H, S, Q> {
+        OwnedTree {
+            arena: self.arena,
+            parents: self.parents,
+            queue: self.queue,
+            buffer: self.buffer,
+            goal,
+        }
+    }
+
+    /// Hashes states with `hasher` from now on, keeping their ids.
+    pub fn with_hasher<R: BuildHasher + Default>(self, hasher: R) -> OwnedTree<T, G, R, Q> {
+        OwnedTree {
+            arena: self.arena.rehash(hasher),
+            parents: self.parents,
+            queue: self.queue,
+            buffer: self.buffer,
+            goal: self.goal,
+        }
+    }
+
+    /// Queues ids in `open` from now on, moving over any already queued. It
+    /// must pop them first-in first-out.
+    pub fn with_open_list<R: OpenList<u32>>(mut self, mut open: R) -> OwnedTree<T, G, S, R> {
+        while let Some(id) = self.queue.pop() {
+            open.push(id);
+        }
+        OwnedTree {
+            arena: self.arena,
+            parents: self.parents,
+            queue: open,
+            buffer: self.buffer,
+            goal: self.goal,
+        }
+    }
+
+    pub fn arena(&self) -> &Arena<T, S> {
+        &self.arena
+    }
+
+    /// Ids of the states from the start to `id`.
+    pub fn path_ids(&self, id: u32) -> Vec<u32> {
+        let mut result = vec![id];
+        let mut current = id;
+        while self.parents[current as usize] != ROOT {
+            current = self.parents[current as usize];
+            result.push(current);
+        }
+        result.reverse();
+        result
+    }
+
+    /// Returns the path to the next goal in breadth-first order; calling
+    /// `run` again continues towards the next goal, as with `Tree`.
+    pub fn run(&mut self) -> Option<Vec<&T>> {
+        while let Some(current) = self.queue.pop() {
+            // `neighbors` borrows from the arena, so successors are buffered
+            // before any of them is interned.
+            let mut successors = core::mem::take(&mut self.buffer);
+            successors.extend(self.arena.get(current).neighbors());
+            for t in successors.drain(..) {
+                let (id, new) = self.arena.intern(t);
+                if new {
+                    self.parents.push(current);
+                    self.queue.push(id);
+                }
+            }
+            self.buffer = successors;
+            if self.goal.is_goal(self.arena.get(current)) {
+                let path = self.path_ids(current);
+                return Some(path.into_iter().map(|id| self.arena.get(id)).collect());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Discs;
+
+    #[test]
+    fn test_hanoi() {
+        let mut tree = OwnedTree::new(Discs([0u8; 6]));
+        let path = tree.run().unwrap();
+        assert_eq!(64, path.len());
+        assert_eq!(Discs([0; 6]), *path[0]);
+        assert!(path.last().unwrap().is_goal());
+    }
+
+    #[test]
+    fn test_goal_and_arena() {
+        let mut tree = OwnedTree::new(Discs([0u8; 3])).with_goal(|d: &Discs<3>| d.0 == [1, 1, 1]);
+        assert_eq!(8, tree.run().unwrap().len());
+        // Rehashing part way keeps ids, so the search carries on unchanged.
+        let mut tree = tree.with_hasher(std::hash::RandomState::new());
+        assert_eq!(Some(0), tree.arena().find(&Discs([0; 3])));
+        while tree.run().is_some() {}
+        assert_eq!(27, tree.arena().len());
+    }
+}
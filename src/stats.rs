@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchStats {
+    pub expanded: usize,
+    /// Successors returned by `neighbors`, duplicates included.
+    pub generated: usize,
+    /// Queued states skipped because they had already been expanded.
+    pub duplicates: usize,
+    pub visited: usize,
+    pub peak_frontier: usize,
+    /// Depth of the most recently expanded state.
+    pub depth: usize,
+    /// Per-depth counts, indexed by distance from the start.
+    pub layers: Vec<LayerStats>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerStats {
+    pub expanded: usize,
+    pub generated: usize,
+}
+
+impl LayerStats {
+    /// Mean number of successors per expanded state in this layer.
+    pub fn branching_factor(&self) -> f64 {
+        if self.expanded == 0 {
+            0.0
+        } else {
+            self.generated as f64 / self.expanded as f64
+        }
+    }
+}
+
+/// Callbacks fired by `Tree` while it searches. Every method does nothing by
+/// default.
+pub trait Observer<T> {
+    fn on_expand(&mut self, _state: &Rc<T>, _depth: usize) {}
+    fn on_duplicate(&mut self, _state: &Rc<T>) {}
+    fn on_goal(&mut self, _path: &[Rc<T>]) {}
+    /// Called once every state at `depth` has been expanded.
+    fn on_depth_complete(&mut self, _depth: usize, _stats: &SearchStats) {}
+}
+
+impl<T> Observer<T> for () {}
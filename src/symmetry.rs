@@ -0,0 +1,180 @@
+use ahash::{HashMap, HashMapExt};
+use core::hash::Hash;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::{ClosedSet, Goal, OpenList, State};
+
+/// A `State` with a key shared by all states that are equivalent under some
+/// symmetry, e.g. the same Towers position with two target pegs swapped.
+pub trait Canonical: State {
+    type Key: Hash + Eq;
+
+    fn canonical_key(&self) -> Self::Key;
+}
+
+/// Breadth-first search that deduplicates by `canonical_key`, so only one
+/// representative of each class of equivalent states is expanded. The goal
+/// must hold for either all or none of the states in a class.
+pub struct SymmetricTree<
+    T: Canonical,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<<T as Canonical>::Key, Option<Rc<T>>> = HashMap<
+        <T as Canonical>::Key,
+        Option<Rc<T>>,
+    >,
+    Q: OpenList<(Rc<T>, Option<Rc<T>>)> = VecDeque<(Rc<T>, Option<Rc<T>>)>,
+> {
+    queue: Q,
+    // Parent of the representative expanded for each key.
+    visited: C,
+    goal: G,
+    buffer: Vec<Rc<T>>,
+}
+
+impl<T: Canonical> SymmetricTree<T> {
+    pub fn new(start: Rc<T>) -> SymmetricTree<T> {
+        let mut tree = SymmetricTree {
+            queue: VecDeque::new(),
+            visited: HashMap::new(),
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            buffer: Vec::new(),
+        };
+        tree.queue.push((start, None));
+        tree
+    }
+}
+
+impl<T, G, C, Q> SymmetricTree<T, G, C, Q>
+where
+    T: Canonical,
+    G: Goal<T>,
+    C: ClosedSet<T::Key, Option<Rc<T>>>,
+    Q: OpenList<(Rc<T>, Option<Rc<T>>)>,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> SymmetricTree<T, H, C, Q> {
+        SymmetricTree {
+            queue: self.queue,
+            visited: self.visited,
+            goal,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Stores expanded keys in `closed` from now on, moving over any already
+    /// there.
+    pub fn with_closed_set<D>(self, mut closed: D) -> SymmetricTree<T, G, D, Q>
+    where
+        D: ClosedSet<T::Key, Option<Rc<T>>>,
+        T::Key: Clone,
+    {
+        for (key, prev) in self.visited.iter() {
+            closed.insert(key.clone(), prev.clone());
+        }
+        SymmetricTree {
+            queue: self.queue,
+            visited: closed,
+            goal: self.goal,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Queues states in `open` from now on, moving over any already queued.
+    pub fn with_open_list<R>(mut self, mut open: R) -> SymmetricTree<T, G, C, R>
+    where
+        R: OpenList<(Rc<T>, Option<Rc<T>>)>,
+    {
+        while let Some(entry) = self.queue.pop() {
+            open.push(entry);
+        }
+        SymmetricTree {
+            queue: open,
+            visited: self.visited,
+            goal: self.goal,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Number of equivalence classes reached so far.
+    pub fn len(&self) -> usize {
+        self.visited.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visited.is_empty()
+    }
+
+    // Every state on the path is a representative that was expanded, so each
+    // one is a real successor of the one before.
+    fn get_path_to_node(&self, node: &Rc<T>) -> Vec<Rc<T>> {
+        let mut result = vec![Rc::clone(node)];
+        let mut current = Rc::clone(node);
+        while let Some(Some(prev)) = self.visited.get(&current.canonical_key()) {
+            current = Rc::clone(prev);
+            result.push(Rc::clone(&current));
+        }
+        result.reverse();
+        result
+    }
+
+    pub fn run(&mut self) -> Option<Vec<Rc<T>>> {
+        while let Some((current, prev)) = self.queue.pop() {
+            let key = current.canonical_key();
+            if self.visited.contains(&key) {
+                continue;
+            }
+            self.visited.insert(key, prev);
+            current.neighbors_into(&mut self.buffer);
+            for t in self.buffer.drain(..) {
+                self.queue.push((t, Some(Rc::clone(&current))));
+            }
+            if self.goal.is_goal(&current) {
+                return Some(self.get_path_to_node(&current));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{is_path, Towers};
+    use crate::Tree;
+
+    // With the goal below, the three pegs other than the first are
+    // interchangeable.
+    impl Canonical for Towers {
+        type Key = Vec<Vec<usize>>;
+
+        fn canonical_key(&self) -> Vec<Vec<usize>> {
+            let mut key = self.pegs.clone();
+            key[1..].sort();
+            key
+        }
+    }
+
+    fn stacked_elsewhere(t: &Towers) -> bool {
+        t.pegs[0].is_empty() && t.pegs.iter().filter(|p| !p.is_empty()).count() == 1
+    }
+
+    #[test]
+    fn test_symmetric_hanoi() {
+        for d in 1..6 {
+            let start = Rc::new(Towers::new(4, d));
+            let mut tree = Tree::new(Rc::clone(&start)).with_goal(stacked_elsewhere);
+            let mut symmetric = SymmetricTree::new(start).with_goal(stacked_elsewhere);
+            let expected = tree.run().unwrap();
+            let path = symmetric.run().unwrap();
+            assert_eq!(expected.len(), path.len());
+            assert!(stacked_elsewhere(path.last().unwrap()));
+            assert!(is_path(&path));
+
+            while tree.run().is_some() {}
+            while symmetric.run().is_some() {}
+            assert_eq!(4usize.pow(d as u32), tree.stats().visited);
+            // Close to a sixth of the states, one per ordering of three pegs.
+            assert_eq!([2, 5, 15, 51, 187][d - 1], symmetric.len());
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! An `Arc`-based `State` that can be shared across threads, a basic
+//! breadth-first `Tree` over it, and a breadth-first search that expands each
+//! layer in parallel.
+
+use ahash::{HashMap, HashMapExt, RandomState};
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::ops::Range;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{ClosedSet, Goal, OpenList};
+
+pub trait State: Hash + PartialEq + Eq + Send + Sync {
+    fn neighbors(&self) -> Vec<Arc<Self>>;
+    fn is_goal(&self) -> bool;
+}
+
+/// Breadth-first search over `sync::State`, returning the same paths as
+/// `crate::Tree::run`. Only goals and storage can be configured; limits,
+/// statistics, observers and stepping are available on `crate::Tree` alone.
+pub struct Tree<
+    T: State,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Arc<T>, Option<Arc<T>>> = HashMap<Arc<T>, Option<Arc<T>>>,
+    Q: OpenList<(Arc<T>, Option<Arc<T>>)> = VecDeque<(Arc<T>, Option<Arc<T>>)>,
+> {
+    queue: Q,
+    visited: C,
+    goal: G,
+    state: PhantomData<T>,
+}
+
+impl<T: State> Tree<T> {
+    pub fn new(start: Arc<T>) -> Tree<T> {
+        let mut tree = Tree {
+            queue: VecDeque::new(),
+            visited: HashMap::new(),
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            state: PhantomData,
+        };
+        tree.queue.push((start, None));
+        tree
+    }
+}
+
+impl<T, G, C, Q> Tree<T, G, C, Q>
+where
+    T: State,
+    G: Goal<T>,
+    C: ClosedSet<Arc<T>, Option<Arc<T>>>,
+    Q: OpenList<(Arc<T>, Option<Arc<T>>)>,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> Tree<T, H, C, Q> {
+        Tree {
+            queue: self.queue,
+            visited: self.visited,
+            goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_closed_set<D>(self, mut closed: D) -> Tree<T, G, D, Q>
+    where
+        D: ClosedSet<Arc<T>, Option<Arc<T>>>,
+    {
+        for (t, prev) in self.visited.iter() {
+            closed.insert(Arc::clone(t), prev.clone());
+        }
+        Tree {
+            queue: self.queue,
+            visited: closed,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_open_list<R>(mut self, mut open: R) -> Tree<T, G, C, R>
+    where
+        R: OpenList<(Arc<T>, Option<Arc<T>>)>,
+    {
+        while let Some(entry) = self.queue.pop() {
+            open.push(entry);
+        }
+        Tree {
+            queue: open,
+            visited: self.visited,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    pub fn run(&mut self) -> Option<Vec<Arc<T>>> {
+        while let Some((current, prev)) = self.queue.pop() {
+            if self.visited.contains(&current) {
+                continue;
+            }
+            self.visited.insert(Arc::clone(&current), prev);
+            for t in current.neighbors() {
+                self.queue.push((t, Some(Arc::clone(&current))));
+            }
+            if self.goal.is_goal(&current) {
+                return Some(path_to(|t| self.visited.get(t).cloned(), &current));
+            }
+        }
+        None
+    }
+}
+
+fn path_to<T, F: Fn(&Arc<T>) -> Option<Option<Arc<T>>>>(parent: F, node: &Arc<T>) -> Vec<Arc<T>> {
+    let mut result = vec![Arc::clone(node)];
+    let mut current = Arc::clone(node);
+    while let Some(Some(prev)) = parent(&current) {
+        result.push(Arc::clone(&prev));
+        current = prev;
+    }
+    result.reverse();
+    result
+}
+
+/// What `ParallelBfs` records for each visited state.
+pub struct Visit<T> {
+    parent: Option<Arc<T>>,
+    depth: usize,
+    // Position of the first discovery in serial breadth-first order: index of
+    // the parent in its layer, then index among the parent's neighbors.
+    order: (usize, usize),
+}
+
+// A visited map split into independently locked shards. The hasher only
+// picks the shard for a state.
+struct Shards<C> {
+    hasher: RandomState,
+    shards: Vec<Mutex<C>>,
+}
+
+impl<C> Shards<C> {
+    fn new(count: usize, shard: impl Fn() -> C) -> Shards<C> {
+        Shards {
+            hasher: RandomState::new(),
+            shards: (0..count).map(|_| Mutex::new(shard())).collect(),
+        }
+    }
+
+    fn shard<T: State>(&self, state: &T) -> &Mutex<C> {
+        let index = self.hasher.hash_one(state) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    fn parent<T>(&self, state: &Arc<T>) -> Option<Option<Arc<T>>>
+    where
+        T: State,
+        C: ClosedSet<Arc<T>, Visit<T>>,
+    {
+        let shard = self.shard(&**state).lock().unwrap();
+        shard.get(state).map(|v| v.parent.clone())
+    }
+
+    fn order<T>(&self, state: &Arc<T>) -> (usize, usize)
+    where
+        T: State,
+        C: ClosedSet<Arc<T>, Visit<T>>,
+    {
+        let shard = self.shard(&**state).lock().unwrap();
+        shard.get(state).unwrap().order
+    }
+}
+
+/// Level-synchronous breadth-first search. A pool of worker threads is
+/// started once per `run`, each layer is split between them, and successors
+/// are deduplicated in a visited map split into shards of type `C`, built by
+/// `F`. Ties are broken by serial discovery order, so the path returned is the
+/// one `sync::Tree` would return.
+pub struct ParallelBfs<
+    T: State,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Arc<T>, Visit<T>> + Send = HashMap<Arc<T>, Visit<T>>,
+    F: Fn() -> C = fn() -> C,
+> {
+    start: Arc<T>,
+    goal: G,
+    threads: usize,
+    shard: F,
+}
+
+impl<T: State> ParallelBfs<T> {
+    pub fn new(start: Arc<T>) -> ParallelBfs<T> {
+        ParallelBfs {
+            start,
+            goal: <T as State>::is_goal as fn(&T) -> bool,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            shard: HashMap::default as fn() -> HashMap<Arc<T>, Visit<T>>,
+        }
+    }
+}
+
+impl<T, G, C, F> ParallelBfs<T, G, C, F>
+where
+    T: State,
+    G: Goal<T>,
+    C: ClosedSet<Arc<T>, Visit<T>> + Send,
+    F: Fn() -> C,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> ParallelBfs<T, H, C, F> {
+        ParallelBfs {
+            start: self.start,
+            goal,
+            threads: self.threads,
+            shard: self.shard,
+        }
+    }
+
+    /// Calls `shard` for each shard of the visited map when a run starts;
+    /// there are four shards per thread.
+    pub fn with_closed_set<D, E>(self, shard: E) -> ParallelBfs<T, G, D, E>
+    where
+        D: ClosedSet<Arc<T>, Visit<T>> + Send,
+        E: Fn() -> D,
+    {
+        ParallelBfs {
+            start: self.start,
+            goal: self.goal,
+            threads: self.threads,
+            shard,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> ParallelBfs<T, G, C, F> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// A panic in `neighbors` on a worker thread is raised again here once
+    /// the other workers have stopped.
+    pub fn run(&mut self) -> Option<Vec<Arc<T>>> {
+        let visited = Shards::new(self.threads * 4, &self.shard);
+        let root = Visit {
+            parent: None,
+            depth: 0,
+            order: (0, 0),
+        };
+        visited
+            .shard(&*self.start)
+            .lock()
+            .unwrap()
+            .insert(Arc::clone(&self.start), root);
+        // The workers live for the whole search; each layer is split into one
+        // job per worker and their results are gathered before the next. A
+        // panic in a worker is caught and sent back, to be raised again here.
+        thread::scope(|scope| {
+            let (done, results) = mpsc::channel();
+            let workers: Vec<Sender<Job<T>>> = (0..self.threads)
+                .map(|_| {
+                    let (jobs, received) = mpsc::channel::<Job<T>>();
+                    let done = done.clone();
+                    let visited = &visited;
+                    scope.spawn(move || {
+                        for job in received {
+                            let found = panic::catch_unwind(AssertUnwindSafe(|| job.run(visited)));
+                            if done.send(found).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    jobs
+                })
+                .collect();
+            drop(done);
+            let mut frontier = Arc::new(vec![Arc::clone(&self.start)]);
+            let mut depth = 0;
+            while !frontier.is_empty() {
+                if let Some(goal) = frontier.iter().find(|t| self.goal.is_goal(t)) {
+                    return Some(path_to(|t| visited.parent(t), goal));
+                }
+                let chunk = frontier.len().div_ceil(self.threads);
+                let mut sent = 0;
+                for (worker, start) in workers.iter().zip((0..frontier.len()).step_by(chunk)) {
+                    let job = Job {
+                        frontier: Arc::clone(&frontier),
+                        range: start..(start + chunk).min(frontier.len()),
+                        depth,
+                    };
+                    worker.send(job).unwrap();
+                    sent += 1;
+                }
+                let mut found = Vec::new();
+                for _ in 0..sent {
+                    match results.recv().expect("worker thread exited") {
+                        Ok(part) => found.extend(part),
+                        Err(payload) => panic::resume_unwind(payload),
+                    }
+                }
+                found.sort_by_cached_key(|t| visited.order(t));
+                frontier = Arc::new(found);
+                depth += 1;
+            }
+            None
+        })
+    }
+}
+
+// A slice of one layer for a worker to expand.
+struct Job<T> {
+    frontier: Arc<Vec<Arc<T>>>,
+    range: Range<usize>,
+    depth: usize,
+}
+
+impl<T: State> Job<T> {
+    // Expands the states in `range` and returns those seen for the first
+    // time, recording for every successor its earliest discovery in serial
+    // order.
+    fn run<C: ClosedSet<Arc<T>, Visit<T>>>(&self, visited: &Shards<C>) -> Vec<Arc<T>> {
+        let depth = self.depth;
+        let mut found = Vec::new();
+        for index in self.range.clone() {
+            let current = &self.frontier[index];
+            for (j, t) in current.neighbors().into_iter().enumerate() {
+                let mut shard = visited.shard(&*t).lock().unwrap();
+                match shard.get_mut(&t) {
+                    Some(v) => {
+                        if v.depth == depth + 1 && (index, j) < v.order {
+                            v.parent = Some(Arc::clone(current));
+                            v.order = (index, j);
+                        }
+                    }
+                    None => {
+                        let visit = Visit {
+                            parent: Some(Arc::clone(current)),
+                            depth: depth + 1,
+                            order: (index, j),
+                        };
+                        shard.insert(Arc::clone(&t), visit);
+                        found.push(t);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Towers;
+    use std::collections::BTreeMap;
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_hanoi() {
+        for d in 1..6 {
+            let start = Arc::new(Towers::new(3, d));
+            let path = ParallelBfs::new(start).with_threads(4).run().unwrap();
+            assert_eq!(2usize.pow(d as u32) - 1, path.len() - 1);
+        }
+    }
+
+    #[test]
+    fn test_same_as_serial() {
+        // Four pegs have many shortest solutions, so this checks tie-breaking.
+        for d in 1..5 {
+            let start = Arc::new(Towers::new(4, d));
+            let serial = Tree::new(Arc::clone(&start)).run();
+            for threads in [1, 3, 8] {
+                let parallel = ParallelBfs::new(Arc::clone(&start))
+                    .with_threads(threads)
+                    .run();
+                assert_eq!(serial, parallel);
+            }
+            let ordered = ParallelBfs::new(Arc::clone(&start))
+                .with_threads(3)
+                .with_closed_set(BTreeMap::new)
+                .run();
+            assert_eq!(serial, ordered);
+        }
+    }
+
+    // Counts up from zero and panics when expanding 3.
+    #[derive(Hash, PartialEq, Eq, Debug)]
+    struct Fragile(u32);
+
+    impl State for Fragile {
+        fn neighbors(&self) -> Vec<Arc<Fragile>> {
+            if self.0 == 3 {
+                panic!("fragile");
+            }
+            vec![Arc::new(Fragile(self.0 + 1)), Arc::new(Fragile(self.0 + 2))]
+        }
+
+        fn is_goal(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_worker_panic() {
+        let (sender, receiver) = mpsc::channel();
+        let search = thread::spawn(move || {
+            let path = ParallelBfs::new(Arc::new(Fragile(0))).with_threads(2).run();
+            sender.send(path).unwrap();
+        });
+        // The panic reaches the caller instead of leaving `run` blocked.
+        let outcome = receiver.recv_timeout(Duration::from_secs(10));
+        assert_eq!(Err(RecvTimeoutError::Disconnected), outcome);
+        let payload = search.join().unwrap_err();
+        assert_eq!(Some(&"fragile"), payload.downcast_ref::<&str>());
+    }
+
+    #[test]
+    fn test_send() {
+        let start = Arc::new(Towers::new(3, 3));
+        let path = thread::spawn(move || Tree::new(start).run())
+            .join()
+            .unwrap();
+        assert_eq!(8, path.unwrap().len());
+    }
+}
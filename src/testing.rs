@@ -0,0 +1,233 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{
+    strip_labels, sync, Heuristic, Labeled, OwnedState, Packed, Reversible, State, Undirected,
+    WeightedState,
+};
+
+#[derive(Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Towers {
+    pub(crate) pegs: Vec<Vec<usize>>,
+}
+
+impl Towers {
+    pub(crate) fn new(pegs: usize, discs: usize) -> Towers {
+        let mut result = Towers { pegs: Vec::new() };
+        result.pegs.push((0..discs).collect::<Vec<usize>>());
+        for _ in 1..pegs {
+            result.pegs.push(Vec::new());
+        }
+        result
+    }
+
+    pub(crate) fn move_disc(&self, from: usize, to: usize) -> Option<Towers> {
+        if from == to
+            || from >= self.pegs.len()
+            || to >= self.pegs.len()
+            || self.pegs[from].is_empty()
+            || (!self.pegs[to].is_empty()
+                && self.pegs[from].last().unwrap() < self.pegs[to].last().unwrap())
+        {
+            return None;
+        }
+        let mut result = self.clone();
+        let moved = result.pegs[from].pop().unwrap();
+        result.pegs[to].push(moved);
+        Some(result)
+    }
+}
+
+impl State for Towers {
+    fn neighbors(&self) -> Vec<Rc<Towers>> {
+        strip_labels(self)
+    }
+
+    fn is_goal(&self) -> bool {
+        for i in 0..(self.pegs.len() - 1) {
+            if !self.pegs[i].is_empty() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl sync::State for Towers {
+    fn neighbors(&self) -> Vec<Arc<Towers>> {
+        let mut result = Vec::new();
+        for i in 0..self.pegs.len() {
+            for j in 0..self.pegs.len() {
+                if let Some(neighbor) = self.move_disc(i, j) {
+                    result.push(Arc::new(neighbor));
+                }
+            }
+        }
+        result
+    }
+
+    fn is_goal(&self) -> bool {
+        State::is_goal(self)
+    }
+}
+
+impl WeightedState for Towers {
+    type Cost = usize;
+
+    fn neighbors(&self) -> Vec<(Rc<Towers>, usize)> {
+        State::neighbors(self).into_iter().map(|t| (t, 1)).collect()
+    }
+
+    fn is_goal(&self) -> bool {
+        State::is_goal(self)
+    }
+}
+
+impl Heuristic for Towers {
+    fn heuristic(&self) -> usize {
+        let last = self.pegs.len() - 1;
+        self.pegs[..last].iter().map(|peg| peg.len()).sum()
+    }
+}
+
+impl Reversible for Towers {}
+
+impl Undirected for Towers {}
+
+impl Labeled for Towers {
+    type Action = (usize, usize);
+
+    fn labeled_neighbors(&self) -> Vec<((usize, usize), Rc<Towers>)> {
+        let mut result = Vec::new();
+        for i in 0..self.pegs.len() {
+            for j in 0..self.pegs.len() {
+                if let Some(neighbor) = self.move_disc(i, j) {
+                    result.push(((i, j), Rc::new(neighbor)));
+                }
+            }
+        }
+        result
+    }
+}
+
+// A finite cycle of states with no goal in it.
+#[derive(Hash, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Ring {
+    at: usize,
+    size: usize,
+}
+
+impl Ring {
+    pub(crate) fn new(size: usize) -> Ring {
+        Ring { at: 0, size }
+    }
+}
+
+impl State for Ring {
+    fn neighbors(&self) -> Vec<Rc<Ring>> {
+        let size = self.size;
+        vec![
+            Rc::new(Ring {
+                at: (self.at + 1) % size,
+                size,
+            }),
+            Rc::new(Ring {
+                at: (self.at + size - 1) % size,
+                size,
+            }),
+        ]
+    }
+
+    fn is_goal(&self) -> bool {
+        false
+    }
+}
+
+impl WeightedState for Ring {
+    type Cost = usize;
+
+    fn neighbors(&self) -> Vec<(Rc<Ring>, usize)> {
+        State::neighbors(self).into_iter().map(|t| (t, 1)).collect()
+    }
+
+    fn is_goal(&self) -> bool {
+        false
+    }
+}
+
+impl Heuristic for Ring {
+    fn heuristic(&self) -> usize {
+        0
+    }
+}
+
+impl Reversible for Ring {}
+
+// Towers of Hanoi on three pegs as the peg of each disc, smallest disc first.
+// Small and `Copy`, for the drivers that store states by value or on disk.
+#[derive(Hash, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Discs<const N: usize>(pub(crate) [u8; N]);
+
+impl<const N: usize> Discs<N> {
+    fn top(&self, peg: u8) -> Option<usize> {
+        self.0.iter().position(|&p| p == peg)
+    }
+
+    fn moves(&self) -> impl Iterator<Item = Discs<N>> + '_ {
+        (0..3u8).flat_map(move |from| {
+            (0..3u8).filter_map(move |to| {
+                let disc = self.top(from)?;
+                if from == to || self.top(to).is_some_and(|t| t < disc) {
+                    return None;
+                }
+                let mut next = *self;
+                next.0[disc] = to;
+                Some(next)
+            })
+        })
+    }
+
+    fn solved(&self) -> bool {
+        self.0.iter().all(|&p| p == 2)
+    }
+}
+
+impl<const N: usize> State for Discs<N> {
+    fn neighbors(&self) -> Vec<Rc<Discs<N>>> {
+        self.moves().map(Rc::new).collect()
+    }
+
+    fn is_goal(&self) -> bool {
+        self.solved()
+    }
+}
+
+impl<const N: usize> OwnedState for Discs<N> {
+    fn neighbors(&self) -> impl Iterator<Item = Discs<N>> {
+        self.moves()
+    }
+
+    fn is_goal(&self) -> bool {
+        self.solved()
+    }
+}
+
+impl<const N: usize> Undirected for Discs<N> {}
+
+impl<const N: usize> Packed for Discs<N> {
+    const SIZE: usize = N;
+
+    fn pack(&self, out: &mut [u8]) {
+        out.copy_from_slice(&self.0);
+    }
+
+    fn unpack(bytes: &[u8]) -> Discs<N> {
+        Discs(bytes.try_into().unwrap())
+    }
+}
+
+// Whether each state in `path` is a successor of the one before it.
+pub(crate) fn is_path<T: State>(path: &[Rc<T>]) -> bool {
+    path.windows(2).all(|w| w[0].neighbors().contains(&w[1]))
+}
@@ -0,0 +1,268 @@
+use ahash::{HashMap, HashMapExt};
+use core::cmp::Ordering;
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::ops::Add;
+use std::collections::BinaryHeap;
+use std::rc::Rc;
+
+use crate::{path_to, ClosedSet, Goal, OpenList};
+
+pub trait Cost: Copy + Ord + Add<Output = Self> {
+    fn zero() -> Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty),*) => {
+        $(impl Cost for $t {
+            fn zero() -> Self {
+                0
+            }
+        })*
+    };
+}
+
+impl_cost!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+pub trait WeightedState: Hash + PartialEq + Eq {
+    type Cost: Cost;
+
+    fn neighbors(&self) -> Vec<(Rc<Self>, Self::Cost)>;
+    fn is_goal(&self) -> bool;
+}
+
+/// A state queued by `Dijkstra` or `AStar`. Entries compare greater the
+/// cheaper they are, with ties broken first-in first-out like `Tree`, so a
+/// `BinaryHeap` of them pops the next state to expand.
+pub struct Entry<T, C> {
+    pub(crate) cost: C,
+    pub(crate) seq: usize,
+    pub(crate) node: Rc<T>,
+    pub(crate) prev: Option<Rc<T>>,
+}
+
+impl<T, C: Ord> PartialEq for Entry<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, C: Ord> Eq for Entry<T, C> {}
+
+impl<T, C: Ord> PartialOrd for Entry<T, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, C: Ord> Ord for Entry<T, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Uniform-cost search. The closed set `C`, open list `Q` and map of best
+/// known costs `B` can be replaced with `with_closed_set`, `with_open_list`
+/// and `with_cost_map`.
+pub struct Dijkstra<
+    T: WeightedState,
+    G: Goal<T> = fn(&T) -> bool,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>> = HashMap<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Entry<T, <T as WeightedState>::Cost>> = BinaryHeap<
+        Entry<T, <T as WeightedState>::Cost>,
+    >,
+    B: ClosedSet<Rc<T>, <T as WeightedState>::Cost> = HashMap<Rc<T>, <T as WeightedState>::Cost>,
+> {
+    heap: Q,
+    best: B,
+    visited: C,
+    seq: usize,
+    goal: G,
+    state: PhantomData<T>,
+}
+
+impl<T: WeightedState> Dijkstra<T> {
+    pub fn new(start: Rc<T>) -> Dijkstra<T> {
+        let mut search = Dijkstra {
+            heap: BinaryHeap::new(),
+            best: HashMap::new(),
+            visited: HashMap::new(),
+            seq: 0,
+            goal: <T as WeightedState>::is_goal as fn(&T) -> bool,
+            state: PhantomData,
+        };
+        search.best.insert(Rc::clone(&start), T::Cost::zero());
+        search.push(start, None, T::Cost::zero());
+        search
+    }
+}
+
+impl<T, G, C, Q, B> Dijkstra<T, G, C, Q, B>
+where
+    T: WeightedState,
+    G: Goal<T>,
+    C: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    Q: OpenList<Entry<T, T::Cost>>,
+    B: ClosedSet<Rc<T>, T::Cost>,
+{
+    pub fn with_goal<H: Goal<T>>(self, goal: H) -> Dijkstra<T, H, C, Q, B> {
+        Dijkstra {
+            heap: self.heap,
+            best: self.best,
+            visited: self.visited,
+            seq: self.seq,
+            goal,
+            state: PhantomData,
+        }
+    }
+
+    /// Stores expanded states in `closed` from now on, moving over any
+    /// already there.
+    pub fn with_closed_set<D>(self, mut closed: D) -> Dijkstra<T, G, D, Q, B>
+    where
+        D: ClosedSet<Rc<T>, Option<Rc<T>>>,
+    {
+        for (t, prev) in self.visited.iter() {
+            closed.insert(Rc::clone(t), prev.clone());
+        }
+        Dijkstra {
+            heap: self.heap,
+            best: self.best,
+            visited: closed,
+            seq: self.seq,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    /// Queues states in `open` from now on, moving over any already queued.
+    /// It must pop the greatest `Entry` first.
+    pub fn with_open_list<R>(mut self, mut open: R) -> Dijkstra<T, G, C, R, B>
+    where
+        R: OpenList<Entry<T, T::Cost>>,
+    {
+        while let Some(entry) = self.heap.pop() {
+            open.push(entry);
+        }
+        Dijkstra {
+            heap: open,
+            best: self.best,
+            visited: self.visited,
+            seq: self.seq,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    /// Keeps the cheapest known cost of each queued state in `costs` from
+    /// now on, moving over any already recorded.
+    pub fn with_cost_map<A>(self, mut costs: A) -> Dijkstra<T, G, C, Q, A>
+    where
+        A: ClosedSet<Rc<T>, T::Cost>,
+    {
+        for (t, &cost) in self.best.iter() {
+            costs.insert(Rc::clone(t), cost);
+        }
+        Dijkstra {
+            heap: self.heap,
+            best: costs,
+            visited: self.visited,
+            seq: self.seq,
+            goal: self.goal,
+            state: PhantomData,
+        }
+    }
+
+    fn push(&mut self, node: Rc<T>, prev: Option<Rc<T>>, cost: T::Cost) {
+        self.heap.push(Entry {
+            cost,
+            seq: self.seq,
+            node,
+            prev,
+        });
+        self.seq += 1;
+    }
+
+    pub fn run(&mut self) -> Option<(Vec<Rc<T>>, T::Cost)> {
+        while let Some(Entry {
+            cost, node, prev, ..
+        }) = self.heap.pop()
+        {
+            if self.visited.contains(&node) {
+                continue;
+            }
+            self.visited.insert(Rc::clone(&node), prev);
+            if self.goal.is_goal(&node) {
+                return Some((path_to(&self.visited, &node), cost));
+            }
+            for (t, step) in node.neighbors() {
+                if self.visited.contains(&t) {
+                    continue;
+                }
+                let next = cost + step;
+                if self.best.get(&t).is_some_and(|&b| b <= next) {
+                    continue;
+                }
+                self.best.insert(Rc::clone(&t), next);
+                self.push(t, Some(Rc::clone(&node)), next);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reach `target` from a number by either adding one (expensive) or
+    // doubling (cheap); the fewest moves and the cheapest moves differ.
+    #[derive(Hash, Clone, PartialEq, Eq, Debug)]
+    struct Number {
+        value: u32,
+        target: u32,
+    }
+
+    impl WeightedState for Number {
+        type Cost = u32;
+
+        fn neighbors(&self) -> Vec<(Rc<Number>, u32)> {
+            let mut result = Vec::new();
+            for (value, cost) in [(self.value + 1, 3), (self.value * 2, 1)] {
+                if value <= self.target {
+                    let target = self.target;
+                    result.push((Rc::new(Number { value, target }), cost));
+                }
+            }
+            result
+        }
+
+        fn is_goal(&self) -> bool {
+            self.value == self.target
+        }
+    }
+
+    #[test]
+    fn test_cheapest_path() {
+        let start = Number {
+            value: 1,
+            target: 12,
+        };
+        let (path, cost) = Dijkstra::new(Rc::new(start)).run().unwrap();
+        let values: Vec<u32> = path.iter().map(|n| n.value).collect();
+        assert_eq!(vec![1, 2, 3, 6, 12], values);
+        assert_eq!(6, cost);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let start = Number {
+            value: 7,
+            target: 5,
+        };
+        assert!(Dijkstra::new(Rc::new(start)).run().is_none());
+    }
+}